@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::emulator::{self, EmulatorSettings};
+use crate::read_rom;
+use crate::rom::{hacks, SaveMode};
+
+const USAGE: &str = "usage: smash_tv_edit --save-and-run <rom> --emulator <executable> [--args <template>]";
+
+// None when the editor window should open
+pub fn run(args: &[String]) -> Option<i32> {
+    if args.is_empty() {
+        return None;
+    }
+
+    let result = match args[0].as_str() {
+        "--save-and-run" => save_and_run(&args[1 ..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    Some(match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    })
+}
+
+fn save_and_run(args: &[String]) -> Result<i32, String> {
+    let mut rom_path = None;
+    let mut emulator = EmulatorSettings::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--emulator" => emulator.executable = args.next().ok_or(USAGE)?.clone(),
+            "--args" => emulator.arguments = args.next().ok_or(USAGE)?.clone(),
+            _ if rom_path.is_none() => rom_path = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let rom_path = rom_path.ok_or(USAGE)?;
+    if emulator.executable.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut rom = read_rom(Path::new(rom_path))?;
+
+    let level_data = rom.get_level_data().map_err(|e| format!("Couldn't read level data: {}", e))?;
+    let hacks = hacks::saved_settings(&rom.hack_settings());
+
+    rom = rom.with_level_data(&level_data, SaveMode::InPlace, &hacks).map_err(|e| format!("Not saved! {}", e))?;
+
+    let scratch = emulator::scratch_rom_path();
+    rom.save_rom(&scratch).map_err(|e| format!("Couldn't write {}: {}", scratch.display(), e))?;
+    println!("Saved to {}", scratch.display());
+
+    let status = emulator.launch(&scratch)?
+        .wait()
+        .map_err(|e| format!("Couldn't wait for {}: {}", emulator.executable, e))?;
+
+    Ok(status.code().unwrap_or(1))
+}
@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+use serde::{Deserialize, Serialize};
+
+pub const ROM_PLACEHOLDER: &str = "{rom}";
+
+// separate from the saved rom, so an emulator holding it open doesn't get in the way of saving
+pub fn scratch_rom_path() -> PathBuf {
+    std::env::temp_dir().join("Smash TV run.sfc")
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EmulatorSettings {
+    pub executable: String,
+    pub arguments: String,
+}
+
+impl Default for EmulatorSettings {
+    fn default() -> Self {
+        Self {
+            executable: String::new(),
+            arguments: ROM_PLACEHOLDER.to_string(),
+        }
+    }
+}
+
+impl EmulatorSettings {
+    pub fn command(&self, rom: &Path) -> Result<Command, String> {
+        if self.executable.trim().is_empty() {
+            return Err("No emulator set up".to_string());
+        }
+
+        let rom = rom.to_string_lossy();
+        let mut command = Command::new(self.executable.trim());
+
+        for argument in self.arguments.split_whitespace() {
+            command.arg(argument.replace(ROM_PLACEHOLDER, &rom));
+        }
+
+        if !self.arguments.contains(ROM_PLACEHOLDER) {
+            command.arg(rom.as_ref());
+        }
+
+        Ok(command)
+    }
+
+    pub fn launch(&self, rom: &Path) -> Result<Child, String> {
+        self.command(rom)?
+            .spawn()
+            .map_err(|e| format!("Couldn't start {}: {}", self.executable.trim(), e))
+    }
+}
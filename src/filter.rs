@@ -0,0 +1,64 @@
+use crate::rom::{circuit_arena_name, enemy_type::EnemyType, lint, LevelData};
+
+#[derive(Default)]
+pub struct ArenaFilter {
+    pub search: String,
+    pub enemy: Option<EnemyType>,
+    pub problems_only: bool,
+    pub modified_only: bool,
+}
+
+impl ArenaFilter {
+    pub fn is_active(&self) -> bool {
+        !self.search.trim().is_empty() || self.enemy.is_some() || self.problems_only || self.modified_only
+    }
+
+    pub fn matches(&self, idx: usize, level: &LevelData, saved: Option<&LevelData>, ranges: &lint::EnemyRanges) -> bool {
+        let search = self.search.trim().to_lowercase();
+
+        let name_matches = search.is_empty()
+            || circuit_arena_name()[idx].to_lowercase().contains(&search)
+            || level.name.to_lowercase().contains(&search);
+
+        let enemy_matches = match &self.enemy {
+            Some(enemy) => level.waves.iter().any(|wave| &wave.enemy == enemy),
+            None => true,
+        };
+
+        name_matches
+            && enemy_matches
+            && (!self.problems_only || !lint::lint(level, ranges).is_empty())
+            && (!self.modified_only || saved != Some(level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::test_rom;
+
+    fn matching(filter: &ArenaFilter, levels: &[LevelData], saved: &[LevelData]) -> Vec<usize> {
+        (0 .. levels.len()).filter(|&idx| filter.matches(idx, &levels[idx], saved.get(idx), &lint::EnemyRanges::new(saved))).collect()
+    }
+
+    #[test]
+    fn filters_arenas() {
+        let saved = test_rom::levels();
+        let mut levels = saved.clone();
+        levels[12].waves[0].count += 1;
+
+        assert_eq!(matching(&ArenaFilter::default(), &levels, &saved).len(), 52);
+
+        let search = ArenaFilter{search: " ARENA 1 ".to_string(), ..ArenaFilter::default()};
+        assert!(matching(&search, &levels, &saved).contains(&0));
+
+        let modified = ArenaFilter{modified_only: true, ..ArenaFilter::default()};
+        assert_eq!(matching(&modified, &levels, &saved), [12]);
+
+        let tanks = ArenaFilter{enemy: Some(EnemyType::Tank), ..ArenaFilter::default()};
+        for idx in matching(&tanks, &levels, &saved) {
+            assert!(levels[idx].waves.iter().any(|wave| wave.enemy == EnemyType::Tank));
+        }
+        assert!(!matching(&tanks, &levels, &saved).is_empty());
+    }
+}
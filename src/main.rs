@@ -1,16 +1,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use eframe::egui::{self, Align2, Color32, ComboBox, Context, DragValue, FontId, Id, LayerId, Order, Style, TextStyle, Visuals};
-use rom::{circuit_arena_name, enemy_type::EnemyType, LevelData, Rom, Wave};
+use filter::ArenaFilter;
+use preferences::Preferences;
+use rom::{charset, circuit_arena_name, enemy_type::EnemyType, hacks::{self, HackSetting, HACKS}, lint::{self, Diagnostic, Field, Severity}, patch::{self, PatchState}, usage, LevelData, Rom, SaveMode, Wave};
 
+mod cli;
+mod emulator;
+mod filter;
+mod preferences;
 mod rom;
 
+const WARNING_COLOR: Color32 = Color32::from_rgb(230, 160, 60);
+const ARENA_WIDTH: f32 = 256.0;
+const ARENA_HEIGHT: f32 = 224.0;
+
 fn main() -> Result<(), eframe::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1000.0, 510.0])
+            .with_inner_size([1000.0, 640.0])
             .with_drag_and_drop(true),
         ..Default::default()
     };
@@ -38,7 +54,21 @@ fn main() -> Result<(), eframe::Error> {
                 ..Style::default()
             };
             cc.egui_ctx.set_style(style);
-            Box::new(Editor{center_text: true, ..Editor::default()})
+
+            let preferences: Preferences = cc.storage
+                .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+                .unwrap_or_default();
+
+            let mut editor = Editor{preferences, ..Editor::default()};
+
+            // pick up where the last session left off
+            if let Some(recent) = editor.preferences.recent_roms.first().cloned() {
+                if recent.path.exists() {
+                    editor.load_rom(&recent.path);
+                }
+            }
+
+            Box::new(editor)
         }),
     )
 }
@@ -46,18 +76,176 @@ fn main() -> Result<(), eframe::Error> {
 #[derive(Default)]
 struct Editor {
     dropped_file: Option<egui::DroppedFile>,
+    // picked from the recent list, opened on the next frame
+    open_rom: Option<std::path::PathBuf>,
     selected_level: u8,
 
     rom: Option<Rom>,
     level_data: Vec<LevelData>,
-    center_text: bool,
+    saved_level_data: Vec<LevelData>,
+    rom_modified: Option<SystemTime>,
+    rom_changed_on_disk: bool,
+    filter: ArenaFilter,
+    last_poll: f64,
+    rom_path: Option<std::path::PathBuf>,
+    preferences: Preferences,
+
+    show_problems: bool,
+    show_usage: bool,
+    // None where the hack's code isn't found
+    hacks: Vec<Option<HackSetting>>,
+    enemy_ranges: lint::EnemyRanges,
+    show_hacks: bool,
+    show_settings: bool,
+    status: Option<Result<String, String>>,
 }
 
 impl Editor {
+    fn load_rom(&mut self, path: &std::path::Path) -> bool {
+        let rom = match read_rom(path) {
+            Ok(rom) => rom,
+            Err(e) => {
+                self.status = Some(Err(e));
+                return false;
+            }
+        };
+
+        self.level_data = match rom.get_level_data() {
+            Ok(level_data) => level_data,
+            Err(e) => {
+                self.status = Some(Err(format!("Couldn't read level data: {}", e)));
+                return false;
+            }
+        };
+
+        self.hacks = rom.hack_settings();
+        self.enemy_ranges = lint::EnemyRanges::new(&self.level_data);
+
+        // remember the arena open in the previous rom before switching
+        if let Some(previous) = &self.rom_path {
+            self.preferences.add_recent_rom(previous, self.selected_level);
+        }
+
+        let last_level = (self.level_data.len() - 1) as u8;
+        self.selected_level = self.preferences.selected_level(path).unwrap_or(0).min(last_level);
+        self.preferences.add_recent_rom(path, self.selected_level);
+
+        self.rom = Some(rom);
+        self.rom_path = Some(path.to_path_buf());
+        self.rom_modified = file_modified(path);
+        self.rom_changed_on_disk = false;
+        self.saved_level_data = self.level_data.clone();
+        self.status = None;
+        true
+    }
+
+    fn mark_saved(&mut self, path: PathBuf) {
+        self.saved_level_data = self.level_data.clone();
+
+        // the save itself isn't a change on disk
+        self.rom_modified = file_modified(&path);
+
+        if self.rom_path.as_ref() != Some(&path) {
+            self.preferences.add_recent_rom(&path, self.selected_level);
+            self.rom_path = Some(path);
+        }
+    }
+
+    fn unsaved_arenas(&self) -> usize {
+        self.level_data.iter().zip(&self.saved_level_data).filter(|(level, saved)| level != saved).count()
+    }
+
+    fn poll_rom_file(&mut self, ctx: &Context) {
+        let Some(path) = &self.rom_path else {
+            return;
+        };
+
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        let now = ctx.input(|i| i.time);
+        if now - self.last_poll < 1.0 {
+            return;
+        }
+        self.last_poll = now;
+
+        let modified = file_modified(path);
+        if modified.is_some() && modified != self.rom_modified {
+            self.rom_changed_on_disk = true;
+        }
+    }
+
+    fn reload_window(&mut self, ctx: &Context) {
+        let Some(path) = self.rom_path.clone() else {
+            return;
+        };
+        let unsaved = self.unsaved_arenas();
+
+        egui::Window::new("Rom changed")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("{} was changed by another program.", path.display()));
+
+            if unsaved > 0 {
+                ui.colored_label(WARNING_COLOR, format!("{} arena(s) have unsaved changes.", unsaved));
+            }
+
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                if ui.button("Reload")
+                .on_hover_text("Loads the changed rom. Unsaved changes are lost.")
+                .clicked() {
+                    self.load_rom(&path);
+                }
+
+                if unsaved > 0 && ui.button("Reload and keep my changes")
+                .on_hover_cursor(egui::CursorIcon::Help)
+                .on_hover_text("Loads the changed rom, then puts the arenas you changed back over it.")
+                .clicked() {
+                    let edited = std::mem::take(&mut self.level_data);
+                    let base = std::mem::take(&mut self.saved_level_data);
+
+                    if self.load_rom(&path) {
+                        self.level_data = rom::merge_level_data(&base, &edited, std::mem::take(&mut self.level_data));
+                    } else { // the reload failed, keep editing what we had
+                        self.level_data = edited;
+                        self.saved_level_data = base;
+                    }
+                }
+
+                if ui.button("Ignore").clicked() {
+                    self.rom_modified = file_modified(&path);
+                    self.rom_changed_on_disk = false;
+                }
+            });
+        });
+    }
+
     fn create_ui(&mut self, ctx: &Context) {
         self.side_panel(ctx);
         self.bottom_panel(ctx);
         self.central_panel(ctx);
+
+        if self.show_problems && self.rom.is_some() {
+            self.problems_window(ctx);
+        }
+
+        if self.show_hacks && self.rom.is_some() && !HACKS.is_empty() {
+            self.hacks_window(ctx);
+        }
+
+        if self.show_usage && self.rom.is_some() {
+            self.usage_window(ctx);
+        }
+
+        if self.show_settings {
+            self.settings_window(ctx);
+        }
+
+        if self.rom_changed_on_disk {
+            self.reload_window(ctx);
+        }
     }
 
     fn side_panel(&mut self, ctx: &Context) {
@@ -65,63 +253,500 @@ impl Editor {
             egui::SidePanel::left("left_panel")
             .resizable(false)
             .show(ctx, |ui| {
+                ui.add_space(4.0);
+                ui.add(egui::TextEdit::singleline(&mut self.filter.search).hint_text("Search arenas").desired_width(150.0));
+
+                let filter = &mut self.filter;
+                ComboBox::from_id_source("enemy_filter")
+                .width(150.0)
+                .selected_text(filter.enemy.as_ref().map_or("Any enemy".to_string(), EnemyType::name))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut filter.enemy, None, "Any enemy");
+
+                    for enemy in EnemyType::enemy_list() {
+                        let name = enemy.name();
+                        ui.selectable_value(&mut filter.enemy, Some(enemy), name);
+                    }
+                });
+
+                ui.checkbox(&mut filter.problems_only, "With problems");
+                ui.checkbox(&mut filter.modified_only, "Unsaved changes");
+
+                if filter.is_active() && ui.button("Clear filters").clicked() {
+                    *filter = ArenaFilter::default();
+                }
+                ui.separator();
+
                 egui::ScrollArea::vertical()
-                .max_height(500.0)
+                .max_height(ui.available_height())
                 .drag_to_scroll(false)
                 .show(ui, |ui| {
+                    let mut shown_circuit = None;
+
                     for (idx, &name) in circuit_arena_name().iter().enumerate() {
-                        if idx == 11 || idx == 29 {
+                        let level = &self.level_data[idx];
+                        if !self.filter.matches(idx, level, self.saved_level_data.get(idx), &self.enemy_ranges) {
+                            continue;
+                        }
+
+                        if shown_circuit.is_some_and(|circuit| circuit != level.circuit) {
                             ui.separator();
                         }
+                        shown_circuit = Some(level.circuit);
 
                         if ui.add(egui::SelectableLabel::new(self.selected_level == idx as u8, name)).clicked() {
                             self.selected_level = idx as u8;
                         }
                     }
+
+                    if shown_circuit.is_none() {
+                        ui.label("No arenas match.");
+                    }
                 });
             });
         }
     }
 
     fn bottom_panel(&mut self, ctx: &Context) {
-        if let Some(rom) = &mut self.rom {
+        // where the rom was saved, which becomes the loaded rom
+        let mut saved_to = None;
+
+        if let (Some(rom), Some(rom_path)) = (&mut self.rom, &self.rom_path) {
             egui::TopBottomPanel::bottom("bottom_panel")
             .show(ctx, |ui|{
                 ui.add_space(7.0);
-                if ui.button("Save changes").clicked() {
-                    rom.save_level_data(&self.level_data);
-                }
+                let hacks = hacks::saved_settings(&self.hacks);
+
+                ui.horizontal_wrapped(|ui| {
+                    if ui.button("Save changes")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text("Overwrites the loaded rom. The first save keeps a copy of the original next to it, ending in .bak.")
+                    .clicked() {
+                        self.status = Some(save_level_data(rom, &self.level_data, self.preferences.save_mode, &hacks, rom_path).map(|()| {
+                            saved_to = Some(rom_path.clone());
+                            format!("Saved to {}", rom_path.display())
+                        }));
+                    }
+
+                    if ui.button("Save as...").clicked() {
+                        let path = rfd::FileDialog::new()
+                        .add_filter("SNES rom", &["sfc", "smc"])
+                        .set_file_name(rom_path.file_name().unwrap_or_default().to_string_lossy())
+                        .save_file();
+
+                        if let Some(path) = path {
+                            self.status = Some(save_level_data(rom, &self.level_data, self.preferences.save_mode, &hacks, &path).map(|()| {
+                                let status = format!("Saved to {}", path.display());
+                                saved_to = Some(path);
+                                status
+                            }));
+                        }
+                    }
+
+                    if ui.button("Save and run")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text("Writes the changes to a scratch copy of the rom and starts the emulator set up in Settings on it.\nThe loaded rom isn't changed.")
+                    .clicked() {
+                        let scratch = emulator::scratch_rom_path();
+
+                        self.status = Some(rom.with_level_data(&self.level_data, self.preferences.save_mode, &hacks)
+                        .map_err(|e| format!("Not saved! {}", e))
+                        .and_then(|run| run.save_rom(&scratch).map_err(|e| format!("Couldn't write {}: {}", scratch.display(), e)))
+                        .and_then(|()| self.preferences.emulator.launch(&scratch))
+                        .map(|_| format!("Running {}", scratch.display())));
+                    }
+
+                    ui.separator();
+
+                    ui.radio_value(&mut self.preferences.save_mode, SaveMode::InPlace, "Keep original layout if possible")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text(concat!(
+                        "Writes level data back to its original location when it fits.\n",
+                        "The rom is only expanded to 1MB when the data has grown too large.",
+                    ));
+                    ui.radio_value(&mut self.preferences.save_mode, SaveMode::Expand, "Always expand rom")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text("Moves level data to bank 10 and expands the rom to 1MB.");
+
+                    ui.separator();
+
+                    if ui.button("Restore original layout")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text(concat!(
+                        "Moves level data back to bank 02, undoes the loader changes and shrinks the rom to 512KB.\n",
+                        "Only possible if the level data fits in its original location.",
+                    )).clicked() {
+                        self.status = Some(match rom.restore_vanilla_layout(&self.level_data) {
+                            Ok(()) => match back_up(rom_path).and_then(|()| rom.save_rom(rom_path)) {
+                                Ok(()) => {
+                                    saved_to = Some(rom_path.clone());
+                                    Ok(format!("Restored original layout, saved to {}", rom_path.display()))
+                                }
+                                Err(e) => Err(format!("Restored, but not saved! Couldn't write {}: {}", rom_path.display(), e)),
+                            },
+                            Err(e) => Err(format!("Not restored! {}", e)),
+                        });
+                    }
+
+                    if ui.button("Export asm...")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text("Writes level data, the loader patch and hacks as asar source, laid out like an expanded rom.")
+                    .clicked() {
+                        let path = rfd::FileDialog::new()
+                        .add_filter("asar source", &["asm"])
+                        .set_file_name("levels.asm")
+                        .save_file();
+
+                        if let Some(path) = path {
+                            self.status = Some(match rom.export_asm(&self.level_data, &hacks) {
+                                Ok(asm) => match std::fs::write(&path, asm) {
+                                    Ok(()) => {
+                                        self.preferences.add_recent_project(&path);
+                                        Ok(format!("Exported to {}", path.display()))
+                                    }
+                                    Err(e) => Err(format!("Couldn't write {}: {}", path.display(), e)),
+                                },
+                                Err(e) => Err(format!("Not exported! {}", e)),
+                            });
+                        }
+                    }
+
+                    if ui.button("Import asm...")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text(concat!(
+                        "Reads level data from asar source, such as a file made with Export asm.\n",
+                        "Only org, db, dw, dl and labels are supported. The rom isn't changed until saving.",
+                    ))
+                    .clicked() {
+                        let path = rfd::FileDialog::new()
+                        .add_filter("asar source", &["asm"])
+                        .pick_file();
+
+                        if let Some(path) = path {
+                            self.status = Some(import_asm(rom, &path).map(|level_data| {
+                                self.level_data = level_data;
+                                self.preferences.add_recent_project(&path);
+                                format!("Imported {}", path.display())
+                            }));
+                        }
+                    }
+
+                    if ui.button("Export symbols...")
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text("Writes labels for the level data in the saved rom to a .sym file for bsnes-plus and a .mlb file for Mesen.")
+                    .clicked() {
+                        let path = rfd::FileDialog::new()
+                        .add_filter("bsnes-plus and Mesen symbols", &["sym", "mlb"])
+                        .set_directory(rom_path.parent().unwrap_or(Path::new(".")))
+                        .set_file_name(rom_path.with_extension("sym").file_name().unwrap_or_default().to_string_lossy())
+                        .save_file();
+
+                        if let Some(path) = path {
+                            let (sym_path, mlb_path) = (path.with_extension("sym"), path.with_extension("mlb"));
+
+                            self.status = Some(match rom.sym_file().and_then(|sym| Ok((sym, rom.mlb_file()?))) {
+                                Ok((sym, mlb)) => match std::fs::write(&sym_path, sym).and_then(|()| std::fs::write(&mlb_path, mlb)) {
+                                    Ok(()) => Ok(format!("Exported {} and {}", sym_path.display(), mlb_path.display())),
+                                    Err(e) => Err(format!("Couldn't write symbols: {}", e)),
+                                },
+                                Err(e) => Err(format!("Not exported! {}", e)),
+                            });
+                        }
+                    }
+
+                    match rom.patch_state(&patch::LEVEL_LOADER) {
+                        Ok(PatchState::Original) => { ui.label("Layout: original"); },
+                        Ok(PatchState::Applied) => { ui.label("Layout: expanded").on_hover_text(patch::LEVEL_LOADER.description); },
+                        Ok(PatchState::Conflict(address)) => {
+                            ui.colored_label(severity_color(Severity::Warning), "Layout: unknown")
+                            .on_hover_text(format!("The level data loader at {} was changed by another hack.", address));
+                        },
+                        Err(_) => (),
+                    }
+
+                    ui.separator();
+
+                    let problem_count: usize = self.level_data.iter().map(|level| lint::lint(level, &self.enemy_ranges).len()).sum();
+                    ui.toggle_value(&mut self.show_problems, format!("Problems ({})", problem_count));
+
+                    ui.toggle_value(&mut self.show_usage, "Enemy usage");
+
+                    ui.menu_button("Recent", |ui| {
+                        ui.label("Roms");
+                        for recent in &self.preferences.recent_roms {
+                            if ui.button(recent.path.display().to_string()).clicked() {
+                                self.open_rom = Some(recent.path.clone());
+                                ui.close_menu();
+                            }
+                        }
+
+                        ui.separator();
+                        ui.label("Projects").on_hover_text("Assembly files imported or exported. Opening one imports its level data.");
+
+                        let mut imported = None;
+                        for path in &self.preferences.recent_projects {
+                            if ui.button(path.display().to_string()).clicked() {
+                                imported = Some(path.clone());
+                                ui.close_menu();
+                            }
+                        }
+
+                        if let Some(path) = imported {
+                            self.status = Some(import_asm(rom, &path).map(|level_data| {
+                                self.level_data = level_data;
+                                self.preferences.add_recent_project(&path);
+                                format!("Imported {}", path.display())
+                            }));
+                        }
+                    });
+
+                    ui.toggle_value(&mut self.show_settings, "Settings");
+
+                    // hidden until the catalogue has hacks checked against the rom
+                    if !HACKS.is_empty() {
+                        let hack_count = self.hacks.iter().flatten().filter(|setting| setting.enabled).count();
+                        ui.toggle_value(&mut self.show_hacks, format!("Hacks ({})", hack_count));
+                    }
+
+                    match &self.status {
+                        Some(Ok(status)) => { ui.label(status); },
+                        Some(Err(status)) => { ui.colored_label(severity_color(Severity::Error), status); },
+                        None => (),
+                    }
+                });
                 ui.add_space(3.0);
             });
         }
+
+        if let Some(path) = saved_to {
+            self.mark_saved(path);
+        }
+    }
+
+    fn problems_window(&mut self, ctx: &Context) {
+        let mut open = self.show_problems;
+
+        egui::Window::new("Problems")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let mut found = false;
+
+                for (level_idx, level) in self.level_data.iter().enumerate() {
+                    let diagnostics = lint::lint(level, &self.enemy_ranges);
+                    if diagnostics.is_empty() {
+                        continue;
+                    }
+                    found = true;
+
+                    ui.strong(circuit_arena_name()[level_idx]);
+
+                    for diagnostic in &diagnostics {
+                        let location = match diagnostic.wave {
+                            Some(wave) => format!("Wave {}: ", wave + 1),
+                            None => String::new(),
+                        };
+
+                        let text = egui::RichText::new(format!("{}{}", location, diagnostic.message))
+                        .color(severity_color(diagnostic.severity));
+
+                        if ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked() {
+                            self.selected_level = level_idx as u8;
+                        }
+                    }
+
+                    ui.add_space(5.0);
+                }
+
+                if !found {
+                    ui.label("No problems found.");
+                }
+            });
+        });
+
+        self.show_problems = open;
+    }
+
+    fn hacks_window(&mut self, ctx: &Context) {
+        let mut open = self.show_hacks;
+
+        egui::Window::new("Hacks")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("hacks_grid").num_columns(2).spacing([20.0, 6.0]).show(ui, |ui| {
+                for (idx, (hack, setting)) in HACKS.iter().zip(&mut self.hacks).enumerate() {
+                    let Some(setting) = setting else {
+                        ui.add_enabled(false, egui::Checkbox::new(&mut false, hack.name))
+                        .on_disabled_hover_text("The code this hack changes wasn't found in this rom.");
+                        ui.end_row();
+                        continue;
+                    };
+
+                    ui.checkbox(&mut setting.enabled, hack.name)
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text(hack.description);
+
+                    // enabled hacks are read back within range, so the clamp can't change a loaded value
+                    if !setting.enabled {
+                        ui.add_enabled(false, egui::Label::new(hack.value_name(setting.value)));
+                    } else if hack.value_names.is_empty() {
+                        ui.add(DragValue::new(&mut setting.value).clamp_range(hack.range.clone()));
+                    } else {
+                        ComboBox::from_id_source(("hack", idx))
+                        .selected_text(hack.value_name(setting.value))
+                        .show_ui(ui, |ui| {
+                            for value in hack.range.clone() {
+                                ui.selectable_value(&mut setting.value, value, hack.value_name(value));
+                            }
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.add_space(5.0);
+            ui.label("Only hacks that were changed are written to the rom when saving.");
+        });
+
+        self.show_hacks = open;
+    }
+
+    fn usage_window(&mut self, ctx: &Context) {
+        let mut open = self.show_usage;
+
+        egui::Window::new("Enemy usage")
+        .open(&mut open)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            if ui.button("Export CSV...").clicked() {
+                let path = rfd::FileDialog::new()
+                .add_filter("CSV", &["csv"])
+                .set_file_name("enemy usage.csv")
+                .save_file();
+
+                if let Some(path) = path {
+                    self.status = Some(match std::fs::write(&path, usage::usage_csv(&self.level_data)) {
+                        Ok(()) => Ok(format!("Exported to {}", path.display())),
+                        Err(e) => Err(format!("Couldn't write {}: {}", path.display(), e)),
+                    });
+                }
+            }
+
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (enemy, entries) in usage::enemy_usage(&self.level_data) {
+                    let id = ui.make_persistent_id(("usage", enemy.to_u8()));
+
+                    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header(ui, |ui| {
+                        ui.label(format!("{} ({})", enemy.name(), entries.len()))
+                        .on_hover_text(format!("Enemy ID {}", enemy.to_u8()));
+                    })
+                    .body(|ui| {
+                        if entries.is_empty() {
+                            ui.label("Not used in any arena.");
+                            return;
+                        }
+
+                        egui::Grid::new(("usage_grid", enemy.to_u8())).striped(true).show(ui, |ui| {
+                            for heading in ["Arena", "Wave", "Count", "Spawn limit", "Cooldown", "Pre-spawned", "Spawn timer"] {
+                                ui.strong(heading);
+                            }
+                            ui.end_row();
+
+                            for usage in &entries {
+                                let wave = usage.data;
+
+                                if ui.add(egui::Label::new(circuit_arena_name()[usage.level]).sense(egui::Sense::click()))
+                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                .clicked() {
+                                    self.selected_level = usage.level as u8;
+                                }
+
+                                ui.label((usage.wave + 1).to_string());
+                                ui.label(wave.count.to_string());
+                                ui.label(wave.spawn_limit.to_string());
+                                ui.label(wave.cooldown_timer.to_string());
+                                ui.label(wave.pre_spawned.to_string());
+                                ui.label(wave.spawn_timer.to_string());
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+            });
+        });
+
+        self.show_usage = open;
+    }
+
+    fn settings_window(&mut self, ctx: &Context) {
+        let mut open = self.show_settings;
+
+        egui::Window::new("Settings")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("settings_grid").num_columns(2).spacing([20.0, 6.0]).show(ui, |ui| {
+                ui.label("Emulator");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.preferences.emulator.executable);
+
+                    if ui.button("Browse...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_file() {
+                            self.preferences.emulator.executable = path.display().to_string();
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Arguments")
+                .on_hover_cursor(egui::CursorIcon::Help)
+                .on_hover_text(format!("{} is replaced with the rom path. It's added at the end if left out.", emulator::ROM_PLACEHOLDER));
+                ui.text_edit_singleline(&mut self.preferences.emulator.arguments);
+                ui.end_row();
+            });
+        });
+
+        self.show_settings = open;
     }
 
     fn central_panel(&mut self, ctx: &Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(rom) = &mut self.rom {
+            if self.rom.is_some() {
                 let level_data = &mut self.level_data[self.selected_level as usize];
+                let diagnostics = lint::lint(level_data, &self.enemy_ranges);
 
                 ui.horizontal(|ui| {
                     ui.label("Arena name:")
                     .on_hover_cursor(egui::CursorIcon::Help)
-                    .on_hover_text("Up to 26 characters (automatically truncated).");
+                    .on_hover_text(concat!(
+                        "Up to 26 characters (automatically truncated).\n",
+                        "Only letters, digits, space and ! # ' . ? are available in the game's font.\n",
+                        "Other bytes from the rom show as {XX} in hex and are saved unchanged.",
+                    ));
                     if ui.text_edit_singleline(&mut level_data.name).lost_focus() {
-                        if self.center_text {
-                            let trimmed = level_data.name.trim();
-
-                            // center to 27 chars to put the extra space from uneven names on the left side.
-                            // most, but not all, default names follow this convention.
-                            level_data.name = format!("{:^27}", trimmed);
-                            level_data.name.pop();
-                        } else {
-                            level_data.name.truncate(26);
-                        }
+                        level_data.name = charset::fit_name(&level_data.name, self.preferences.center_text);
                     };
-                    ui.checkbox(&mut self.center_text, "Automatically center text")
+
+                    ui.checkbox(&mut self.preferences.center_text, "Automatically center text")
                     .on_hover_cursor(egui::CursorIcon::Help)
                     .on_hover_text("Adds space around the inputted text to center it.");
                 });
 
+                let unsupported = charset::unsupported_chars(&level_data.name);
+                if !unsupported.is_empty() {
+                    let list: Vec<String> = unsupported.iter().map(|c| format!("'{}'", c)).collect();
+                    ui.colored_label(WARNING_COLOR, format!(
+                        "Characters not in the game's font will be removed: {}", list.join(", ")
+                    ));
+                }
+
                 ui.add_space(10.0);
 
                 ui.horizontal(|ui| {
@@ -132,44 +757,44 @@ impl Editor {
                         "For example, setting this to 1 makes an arena finish when mines are still present.",
                     ));
                     ui.add(DragValue::new(&mut level_data.waves_remaining));
+                    lint_marker(ui, &diagnostics, None, Field::WavesRemaining);
                 });
 
                 ui.add_space(10.0);
 
-                // show and edit room connections
-                ui.label("Connects to:")
-                .on_hover_cursor(egui::CursorIcon::Help)
-                .on_hover_text("The Up, Right and Down connections of this arena.");
-
-                let direction = ["U", "R", "D"];
-                for (idx, con) in level_data.connections.iter_mut().enumerate() {
-                    ui.horizontal(|ui| {
-                        ui.label(format!("{}:", direction[idx]));
-
-                        let text = match con {
-                            0 => "-",
-                            0xFF => "Goal",
-                            _ => {
-                                let offset = [0, 11, 29];
-                                circuit_arena_name()[*con as usize - 1 + offset[level_data.circuit as usize]]
-                            }
-                        };
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        // show and edit room connections
+                        ui.label("Connects to:")
+                        .on_hover_cursor(egui::CursorIcon::Help)
+                        .on_hover_text("The Up, Right and Down connections of this arena.");
 
-                        ComboBox::from_id_source(idx)
-                        .selected_text(text)
-                        .width(155.0)
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value( con, 0, "-");
+                        let direction = ["U", "R", "D"];
+                        for (idx, con) in level_data.connections.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}:", direction[idx]));
 
-                            let asd = [1..=11, 12..=29, 30..=52];
-                            for arena in asd[level_data.circuit as usize].clone() {
-                                ui.selectable_value( con, arena, circuit_arena_name()[arena as usize - 1]);
-                            }
+                                ComboBox::from_id_source(idx)
+                                .selected_text(connection_name(*con, level_data.circuit))
+                                .width(155.0)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value( con, 0, "-");
 
-                            ui.selectable_value( con, 0xFF, "Goal");
-                        });
+                                    let asd = [1..=11, 12..=29, 30..=52];
+                                    for arena in asd[level_data.circuit as usize].clone() {
+                                        ui.selectable_value( con, arena, circuit_arena_name()[arena as usize - 1]);
+                                    }
+
+                                    ui.selectable_value( con, 0xFF, "Goal");
+                                });
+                            });
+                        }
                     });
-                }
+
+                    ui.add_space(30.0);
+
+                    arena_view(ui, level_data);
+                });
 
                 ui.add_space(10.0);
                 ui.separator();
@@ -201,19 +826,51 @@ impl Editor {
                     ui.end_row();
 
                     for (idx, wave) in level_data.waves.iter_mut().enumerate() {
-                        ComboBox::from_id_source(idx)
-                        .selected_text(wave.enemy.name())
-                        .show_ui(ui, |ui| {
-                            for enemy in EnemyType::enemy_list().iter() {
-                                ui.selectable_value(&mut wave.enemy, enemy.clone(), enemy.name());
-                            }
+                        ui.horizontal(|ui| {
+                            ComboBox::from_id_source(idx)
+                            .selected_text(wave.enemy.name())
+                            .show_ui(ui, |ui| {
+                                let group = |enemy: &EnemyType| (enemy.info().boss, enemy.info().collectible);
+                                for (i, enemy) in EnemyType::enemy_list().iter().enumerate() {
+                                    // bosses and collectibles come after the regular enemies
+                                    if i > 0 && group(enemy) != group(&EnemyType::enemy_list()[i - 1]) {
+                                        ui.separator();
+                                    }
+                                    ui.selectable_value(&mut wave.enemy, enemy.clone(), enemy.name());
+                                }
+
+                                // any other ID, for exploring what unused values do
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    let mut id = wave.enemy.to_u8();
+                                    ui.label("ID:");
+                                    if ui.add(DragValue::new(&mut id)).changed() {
+                                        wave.enemy = EnemyType::from_u8(id);
+                                    }
+                                });
+                            });
+
+                            lint_marker(ui, &diagnostics, Some(idx), Field::Enemy);
                         });
 
-                        ui.add(DragValue::new(&mut wave.count));
-                        ui.add(DragValue::new(&mut wave.spawn_limit));
+                        ui.horizontal(|ui| {
+                            ui.add(DragValue::new(&mut wave.count));
+                            lint_marker(ui, &diagnostics, Some(idx), Field::Count);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(DragValue::new(&mut wave.spawn_limit));
+                            lint_marker(ui, &diagnostics, Some(idx), Field::SpawnLimit);
+                        });
                         ui.add(DragValue::new(&mut wave.unknown));
-                        ui.horizontal(|ui|{ ui.add(DragValue::new(&mut wave.cooldown_timer)); ui.label(format!("{:.02}s", wave.cooldown_timer as f32 / 60.0));});
-                        ui.add(DragValue::new(&mut wave.pre_spawned));
+                        ui.horizontal(|ui|{
+                            ui.add(DragValue::new(&mut wave.cooldown_timer));
+                            ui.label(format!("{:.02}s", wave.cooldown_timer as f32 / 60.0));
+                            lint_marker(ui, &diagnostics, Some(idx), Field::CooldownTimer);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(DragValue::new(&mut wave.pre_spawned));
+                            lint_marker(ui, &diagnostics, Some(idx), Field::PreSpawned);
+                        });
                         ui.horizontal(|ui|{ ui.add(DragValue::new(&mut wave.spawn_timer)); ui.label(format!("{:.02}s", wave.spawn_timer as f32 / 60.0));});
 
                         ui.end_row();
@@ -236,14 +893,27 @@ impl Editor {
                     
                     if ui.button("-")
                     .on_hover_cursor(egui::CursorIcon::Help)
-                    .on_hover_text("Remove wave.\nThe minimum is 1 wave.").clicked() {
-                        if level_data.waves.len() > 1 {
-                            level_data.waves.pop();
-                        }
+                    .on_hover_text("Remove wave.\nThe minimum is 1 wave.").clicked() && level_data.waves.len() > 1 {
+                        level_data.waves.pop();
                     }
                 });
             } else {
                 ui.label("Drag-and-drop a Super Smash T.V. (USA) rom onto the window!");
+
+                if !self.preferences.recent_roms.is_empty() {
+                    ui.add_space(10.0);
+                    ui.label("Or open a recent rom:");
+
+                    for recent in &self.preferences.recent_roms {
+                        if ui.link(recent.path.display().to_string()).clicked() {
+                            self.open_rom = Some(recent.path.clone());
+                        }
+                    }
+                }
+
+                if let Some(Err(status)) = &self.status {
+                    ui.colored_label(severity_color(Severity::Error), status);
+                }
             }
         });
     }
@@ -251,6 +921,7 @@ impl Editor {
 
 impl eframe::App for Editor {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.poll_rom_file(ctx);
         self.create_ui(ctx);
 
         preview_files_being_dropped(ctx);
@@ -258,31 +929,131 @@ impl eframe::App for Editor {
         ctx.input(|i| { // Collect dropped files:
             if !i.raw.dropped_files.is_empty() {
                 self.dropped_file = Some(i.raw.dropped_files[0].clone());
+            }
+        });
 
-                let path = self.dropped_file.as_ref().unwrap().path.as_deref().unwrap();
-                let f = match File::open(path) {
-                    Ok(o) => o,
-                    Err(e) => todo!("{}", e),
-                };
+        if let Some(path) = self.dropped_file.take().and_then(|file| file.path).or(self.open_rom.take()) {
+            self.load_rom(&path);
+        }
+    }
 
-                let file_size = f.metadata().unwrap().len();
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Some(path) = &self.rom_path {
+            self.preferences.add_recent_rom(path, self.selected_level);
+        }
 
-                if file_size < 524288 {
-                    println!("file too small!");
-                } else if file_size > 524288 * 4 { // original size * 4 in case it's been extended already
-                    println!("file too big! probably not a smash tv rom");
-                } else {
-                    let rom = std::fs::read(path).expect("Super Smash T.V. (USA).sfc not found!");
-                    if rom[0] == 0x78 && rom[1] == 0x9C && rom[2] == 0x00 { // super bootleg rom check
-                        self.rom = Some(Rom{rom: rom});
+        eframe::set_value(storage, eframe::APP_KEY, &self.preferences);
+    }
+}
 
-                        self.level_data = self.rom.as_ref().unwrap().get_level_data();
-                    } else {
-                        println!("doesn't seem to be a smash tv rom!");
-                    }
-                }
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn import_asm(rom: &Rom, path: &std::path::Path) -> Result<Vec<LevelData>, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+    rom.import_asm(&source).map_err(|e| format!("Not imported! {}", e))
+}
+
+fn save_level_data(rom: &mut Rom, level_data: &[LevelData], mode: SaveMode, hacks: &[HackSetting], path: &Path) -> Result<(), String> {
+    let saved = rom.with_level_data(level_data, mode, hacks).map_err(|e| format!("Not saved! {}", e))?;
+    back_up(path).and_then(|()| saved.save_rom(path)).map_err(|e| format!("Not saved! Couldn't write {}: {}", path.display(), e))?;
+    *rom = saved;
+    Ok(())
+}
+
+// copies the file to <name>.bak before it's first overwritten, an existing backup is kept
+fn back_up(path: &Path) -> std::io::Result<()> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+
+    if path.exists() && !backup.exists() {
+        std::fs::copy(path, backup)?;
+    }
+    Ok(())
+}
+
+fn read_rom(path: &std::path::Path) -> Result<Rom, String> {
+    let rom = std::fs::read(path).map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+    let file_size = rom.len();
+
+    if file_size < 524288 {
+        Err("File too small!".to_string())
+    } else if file_size > 524288 * 4 { // original size * 4 in case it's been extended already
+        Err("File too big! Probably not a Smash TV rom.".to_string())
+    } else if !Rom::is_smash_tv(&rom) {
+        Err("Doesn't seem to be a Smash TV rom!".to_string())
+    } else {
+        Ok(Rom{rom})
+    }
+}
+
+fn severity_color(severity: Severity) -> Color32 {
+    match severity {
+        Severity::Warning => WARNING_COLOR,
+        Severity::Error => Color32::from_rgb(230, 80, 80),
+    }
+}
+
+fn lint_marker(ui: &mut egui::Ui, diagnostics: &[Diagnostic], wave: Option<usize>, field: Field) {
+    let messages: Vec<&Diagnostic> = diagnostics.iter()
+    .filter(|diagnostic| diagnostic.wave == wave && diagnostic.field == field)
+    .collect();
+
+    // diagnostics are sorted most severe first
+    if let Some(worst) = messages.first() {
+        let text: Vec<&str> = messages.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+
+        ui.colored_label(severity_color(worst.severity), "⚠")
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(text.join("\n"));
+    }
+}
+
+fn connection_name(con: u8, circuit: u8) -> String {
+    match con {
+        0 => "-".to_string(),
+        0xFF => "Goal".to_string(),
+        _ => {
+            let arenas = [1 ..= 11, 12 ..= 29, 30 ..= 52];
+            match arenas.get(circuit as usize).and_then(|arenas| arenas.clone().nth(con as usize - 1)) {
+                Some(arena) => circuit_arena_name()[arena - 1].to_string(),
+                None => format!("Unknown ({})", con),
             }
-        });
+        }
+    }
+}
+
+fn arena_view(ui: &mut egui::Ui, level_data: &LevelData) {
+    let scale = 0.75;
+    let (response, painter) = ui.allocate_painter(egui::vec2(ARENA_WIDTH, ARENA_HEIGHT) * scale, egui::Sense::hover());
+    let rect = response.rect;
+
+    painter.rect(rect, 0.0, Color32::from_gray(24), (1.0, Color32::DARK_GRAY));
+    let font = FontId::new(11.0, egui::FontFamily::Proportional);
+
+    // door centers in arena pixels, and where to anchor their labels
+    let doors = [
+        (ARENA_WIDTH / 2.0, 12.0, Align2::CENTER_TOP, Some(level_data.connections[0])),
+        (ARENA_WIDTH - 12.0, ARENA_HEIGHT / 2.0, Align2::RIGHT_CENTER, Some(level_data.connections[1])),
+        (ARENA_WIDTH / 2.0, ARENA_HEIGHT - 12.0, Align2::CENTER_BOTTOM, Some(level_data.connections[2])),
+        (12.0, ARENA_HEIGHT / 2.0, Align2::LEFT_CENTER, None), // no connection entry for the left door
+    ];
+
+    for (x, y, align, con) in doors {
+        let pos = rect.min + egui::vec2(x, y) * scale;
+        let color = match con {
+            Some(0) | None => Color32::GRAY,
+            Some(_) => Color32::YELLOW,
+        };
+
+        painter.rect_stroke(egui::Rect::from_center_size(pos, egui::vec2(24.0, 24.0) * scale), 2.0, (2.0, color));
+
+        if let Some(con) = con {
+            let text_pos = pos + (rect.center() - pos).normalized() * 14.0;
+            painter.text(text_pos, align, connection_name(con, level_data.circuit), font.clone(), color);
+        }
     }
 }
 
@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{emulator::EmulatorSettings, rom::SaveMode};
+
+const RECENT_LIMIT: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecentRom {
+    pub path: PathBuf,
+    pub selected_level: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Preferences {
+    pub center_text: bool,
+    pub save_mode: SaveMode,
+    pub emulator: EmulatorSettings,
+    pub recent_roms: Vec<RecentRom>, // newest first
+    pub recent_projects: Vec<PathBuf>, // asm files, newest first
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            center_text: true,
+            save_mode: SaveMode::default(),
+            emulator: EmulatorSettings::default(),
+            recent_roms: Vec::new(),
+            recent_projects: Vec::new(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn selected_level(&self, path: &Path) -> Option<u8> {
+        self.recent_roms.iter().find(|recent| recent.path == path).map(|recent| recent.selected_level)
+    }
+
+    pub fn add_recent_rom(&mut self, path: &Path, selected_level: u8) {
+        self.recent_roms.retain(|recent| recent.path != path);
+        self.recent_roms.insert(0, RecentRom{path: path.to_path_buf(), selected_level});
+        self.recent_roms.truncate(RECENT_LIMIT);
+    }
+
+    pub fn add_recent_project(&mut self, path: &Path) {
+        self.recent_projects.retain(|recent| recent != path);
+        self.recent_projects.insert(0, path.to_path_buf());
+        self.recent_projects.truncate(RECENT_LIMIT);
+    }
+}
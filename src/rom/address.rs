@@ -0,0 +1,75 @@
+use std::fmt;
+use std::ops::Add;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct SnesAddr(pub u32);
+
+impl SnesAddr {
+    pub fn new(bank: u8, offset: u16) -> Self {
+        Self((bank as u32) << 16 | offset as u32)
+    }
+
+    pub fn bank(self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    pub fn offset(self) -> u16 {
+        self.0 as u16
+    }
+}
+
+impl Add<usize> for SnesAddr {
+    type Output = Self;
+
+    fn add(self, rhs: usize) -> Self {
+        Self(self.0.wrapping_add(rhs as u32))
+    }
+}
+
+impl fmt::Display for SnesAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:02X}:{:04X}", self.bank(), self.offset())
+    }
+}
+
+pub trait Mapper {
+    fn to_file_offset(&self, address: SnesAddr) -> Option<usize>;
+}
+
+// 32KB banks mapped to $8000-$FFFF, mirrored at banks $80-$FF
+pub struct LoRom;
+
+impl Mapper for LoRom {
+    fn to_file_offset(&self, address: SnesAddr) -> Option<usize> {
+        // low half of banks, and wram at $7E-$7F which isn't mirrored at $FE-$FF
+        if address.offset() < 0x8000 || matches!(address.bank(), 0x7E | 0x7F) {
+            return None;
+        }
+
+        let bank = address.bank() & 0x7F;
+        Some(bank as usize * 0x8000 + (address.offset() & 0x7FFF) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lorom_maps_upper_half_of_banks() {
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x008000)), Some(0));
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x02B5F0)), Some(0x0135F0));
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x108000)), Some(0x080000));
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x82B5F0)), Some(0x0135F0));
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0xFEFFFF)), Some(0x3F7FFF));
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0xFF8000)), Some(0x3F8000));
+    }
+
+    #[test]
+    fn lorom_rejects_non_rom_addresses() {
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x00AA66 & 0x7FFF)), None);
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x7E8000)), None);
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0x7F8000)), None);
+        assert_eq!(LoRom.to_file_offset(SnesAddr(0xFF7FFF)), None);
+    }
+}
@@ -0,0 +1,391 @@
+use std::fmt::Write;
+
+use super::{address::SnesAddr, charset, circuit_arena_name, hacks::{HackSetting, HACKS}, patch::LEVEL_LOADER, LevelData, Rom, RomError, SaveMode, EXPANDED_LEVEL_OFFSET, NAMES};
+
+// written so asar grows the rom to 1MB like modify_rom does
+const EXPANDED_ROM_END: SnesAddr = SnesAddr(0x1FFFFF);
+
+pub fn circuit_label(circuit: u8) -> String {
+    format!("circuit{}", circuit + 1)
+}
+
+pub fn arena_label(level: &LevelData) -> String {
+    format!("circuit{}_arena{:02}", level.circuit + 1, level.arena)
+}
+
+fn addr(address: SnesAddr) -> String {
+    format!("${:06X}", address.0)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("${:02X}", byte)).collect::<Vec<_>>().join(", ")
+}
+
+// smash_tv.asm holds the same lines
+fn loader_patch_asm(asm: &mut String) -> std::fmt::Result {
+    writeln!(asm, "; {}", LEVEL_LOADER.description)?;
+    for write in LEVEL_LOADER.writes {
+        writeln!(asm, "org {} : db {} ; was {}", addr(write.address), hex(write.patched), hex(write.original))?;
+    }
+    writeln!(asm, "org {} : db $00", addr(EXPANDED_ROM_END))
+}
+
+impl Rom {
+    pub fn export_asm(&self, level_data: &[LevelData], hacks: &[HackSetting]) -> Result<String, RomError> {
+        // saving into a copy catches everything that would make saving fail
+        let mut rom = Rom{rom: self.rom.clone()};
+        rom.write_level_data(level_data, SaveMode::Expand)?;
+        rom.apply_hacks(hacks)?;
+
+        let mut asm = String::new();
+
+        let _ = self.write_asm(&mut asm, level_data, hacks);
+        Ok(asm)
+    }
+
+    fn write_asm(&self, asm: &mut String, level_data: &[LevelData], hacks: &[HackSetting]) -> std::fmt::Result {
+        writeln!(asm, "; Smash TV level data, apply with: asar levels.asm rom.sfc")?;
+        writeln!(asm, "lorom")?;
+        writeln!(asm)?;
+
+        loader_patch_asm(asm)?;
+        writeln!(asm)?;
+
+        // checked by export_asm
+        let hacks = self.hack_writes(HACKS, hacks).unwrap_or_default();
+
+        if !hacks.is_empty() {
+            writeln!(asm, "; hacks")?;
+            for (hack, value) in hacks {
+                writeln!(asm, "org {} : db {} ; {}", addr(hack.address + 1), hex(&[value]), hack.name)?;
+            }
+            writeln!(asm)?;
+        }
+
+        writeln!(asm, "org {}", addr(EXPANDED_LEVEL_OFFSET))?;
+        writeln!(asm, "level_table:")?;
+        writeln!(asm, "    dw {}", (0 .. 3).map(circuit_label).collect::<Vec<_>>().join(", "))?;
+
+        for circuit in 0 .. 3 {
+            writeln!(asm, "{}:", circuit_label(circuit))?;
+            writeln!(asm, "    dw $0000 ; tv studio")?;
+
+            for level in level_data.iter().filter(|level| level.circuit == circuit) {
+                writeln!(asm, "    dw {}", arena_label(level))?;
+            }
+        }
+
+        for (idx, level) in level_data.iter().enumerate() {
+            writeln!(asm)?;
+            writeln!(asm, "{}: ; {}", arena_label(level), circuit_arena_name()[idx])?;
+            writeln!(asm, "    db {} ; waves - 1", level.waves.len() - 1)?;
+            writeln!(asm, "    ; enemy, count, spawn limit, unknown, cooldown timer, pre-spawned, spawn timer")?;
+
+            for wave in &level.waves {
+                writeln!(
+                    asm, "    db {} : dw {} : db {}, {} : dw {} : db {} : dw {} ; {}",
+                    hex(&[wave.enemy.to_u8()]), wave.count, wave.spawn_limit, hex(&[wave.unknown]),
+                    wave.cooldown_timer, wave.pre_spawned, wave.spawn_timer, wave.enemy.name(),
+                )?;
+            }
+
+            writeln!(asm, "    db {} ; waves remaining", level.waves_remaining)?;
+        }
+
+        writeln!(asm)?;
+        writeln!(asm, "; connections")?;
+        for level in level_data {
+            // the rom was checked by write_level_data, so the lists can be read
+            if let Ok(list) = self.connection_list(level.circuit) {
+                writeln!(asm, "org {} : db {} ; {}", addr(list + level.arena as usize * 3), hex(&level.connections), arena_label(level))?;
+            }
+        }
+
+        writeln!(asm)?;
+        writeln!(asm, "; names")?;
+        writeln!(asm, "org {}", addr(NAMES))?;
+        for level in level_data {
+            let name = charset::encode(&level.name);
+
+            // bytes without a glyph can't go in a string
+            if name.iter().all(|&b| charset::to_char(b).is_some()) {
+                writeln!(asm, "    db \"{}\" ; {}", charset::decode(&name), arena_label(level))?;
+            } else {
+                writeln!(asm, "    db {} ; {}", hex(&name), arena_label(level))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const MAX_ROM_SIZE: usize = 0x400000;
+
+enum Operand {
+    Number(u32),
+    Label(String),
+    Text(String),
+}
+
+enum Statement {
+    Label(String),
+    Org(SnesAddr),
+    Data { width: usize, operands: Vec<Operand> },
+}
+
+impl Statement {
+    fn size(&self) -> usize {
+        match self {
+            Self::Data{width, operands} => operands.iter().map(|operand| match operand {
+                Operand::Text(text) => text.len(),
+                _ => *width,
+            }).sum(),
+            _ => 0,
+        }
+    }
+}
+
+fn split_outside_strings(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+
+    for (idx, char) in text.char_indices() {
+        if char == '"' {
+            in_string = !in_string;
+        } else if char == separator && !in_string {
+            parts.push(&text[start .. idx]);
+            start = idx + 1;
+        }
+    }
+
+    parts.push(&text[start ..]);
+    parts
+}
+
+fn is_label(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+fn parse_number(text: &str) -> Option<u32> {
+    if let Some(hex) = text.strip_prefix('$') {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = text.strip_prefix('%') {
+        u32::from_str_radix(binary, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    let text = text.trim();
+
+    if let Some(string) = text.strip_prefix('"') {
+        return string.strip_suffix('"')
+            .map(|string| Operand::Text(string.to_string()))
+            .ok_or(format!("Unterminated string {}", text));
+    }
+
+    if let Some(number) = parse_number(text) {
+        Ok(Operand::Number(number))
+    } else if is_label(text) {
+        Ok(Operand::Label(text.to_string()))
+    } else {
+        Err(format!("Can't read value \"{}\"", text))
+    }
+}
+
+fn parse_line(line: &str) -> Result<Vec<Statement>, String> {
+    let code = split_outside_strings(line, ';')[0];
+    let segments = split_outside_strings(code, ':');
+    let mut statements = Vec::new();
+
+    for (idx, segment) in segments.iter().enumerate() {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            continue;
+        }
+
+        let (keyword, rest) = segment.split_once(char::is_whitespace).unwrap_or((segment, ""));
+
+        let statement = match keyword.to_ascii_lowercase().as_str() {
+            "lorom" if rest.is_empty() => continue,
+            "org" => match parse_number(rest.trim()) {
+                Some(address) => Statement::Org(SnesAddr(address)),
+                None => return Err(format!("Can't read address \"{}\"", rest.trim())),
+            },
+            "db" | "dw" | "dl" => {
+                let width = match keyword.to_ascii_lowercase().as_str() {
+                    "db" => 1,
+                    "dw" => 2,
+                    _ => 3,
+                };
+
+                let operands = split_outside_strings(rest, ',').into_iter()
+                    .map(parse_operand)
+                    .collect::<Result<_, _>>()?;
+
+                Statement::Data{width, operands}
+            }
+            // a label is followed by a colon, so it can't be the last segment
+            _ if is_label(segment) && idx + 1 < segments.len() => Statement::Label(segment.to_string()),
+            _ => return Err(format!("Unsupported statement \"{}\", only org, db, dw, dl and labels are", segment)),
+        };
+
+        statements.push(statement);
+    }
+
+    Ok(statements)
+}
+
+impl Rom {
+    pub fn import_asm(&self, source: &str) -> Result<Vec<LevelData>, RomError> {
+        self.assemble(source)?.get_level_data()
+    }
+
+    fn assemble(&self, source: &str) -> Result<Rom, RomError> {
+        let error = |line: usize, message: String| RomError::Asm{line: line + 1, message};
+
+        let mut statements = Vec::new();
+        for (line, text) in source.lines().enumerate() {
+            for statement in parse_line(text).map_err(|message| error(line, message))? {
+                statements.push((line, statement));
+            }
+        }
+
+        // first pass finds label addresses
+        let mut labels = std::collections::HashMap::new();
+        let mut pc = None;
+
+        for (line, statement) in &statements {
+            match statement {
+                Statement::Org(address) => pc = Some(*address),
+                Statement::Label(label) => {
+                    let address = pc.ok_or(error(*line, format!("Label {} comes before any org", label)))?;
+                    if labels.insert(label.as_str(), address).is_some() {
+                        return Err(error(*line, format!("Label {} is defined twice", label)));
+                    }
+                }
+                Statement::Data{..} => pc = pc.map(|pc: SnesAddr| pc + statement.size()),
+            }
+        }
+
+        // second pass writes the data
+        let mut rom = Rom{rom: self.rom.clone()};
+        let mut pc = None;
+
+        for (line, statement) in &statements {
+            let line = *line;
+
+            let Statement::Data{width, operands} = statement else {
+                if let Statement::Org(address) = statement {
+                    pc = Some(*address);
+                }
+                continue;
+            };
+
+            let address: SnesAddr = pc.ok_or(error(line, "Data comes before any org".to_string()))?;
+            let mut bytes = Vec::new();
+
+            for operand in operands {
+                let value = match operand {
+                    Operand::Text(text) => {
+                        bytes.extend_from_slice(text.as_bytes());
+                        continue;
+                    }
+                    Operand::Number(number) => *number,
+                    // pointers to labels use as many low bytes as fit, like asar
+                    Operand::Label(label) => match labels.get(label.as_str()) {
+                        Some(address) => address.0 & (u32::MAX >> (32 - width * 8)),
+                        None => return Err(error(line, format!("Unknown label {}", label))),
+                    },
+                };
+
+                if value >> (width * 8) != 0 {
+                    return Err(error(line, format!("{} doesn't fit in {} byte(s)", value, width)));
+                }
+                bytes.extend_from_slice(&value.to_le_bytes()[.. *width]);
+            }
+
+            rom.write_growing(address, &bytes).map_err(|e| error(line, e.to_string()))?;
+            pc = Some(address + bytes.len());
+        }
+
+        Ok(rom)
+    }
+
+    fn write_growing(&mut self, address: SnesAddr, bytes: &[u8]) -> Result<(), RomError> {
+        let end = self.file_offset(address)? + bytes.len();
+
+        if end > MAX_ROM_SIZE {
+            return Err(RomError::OutOfBounds(end));
+        }
+        if end > self.rom.len() {
+            self.rom.resize(end, 0);
+        }
+
+        self.write(address, bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_rom;
+
+    #[test]
+    fn exports_every_arena() {
+        let levels = test_rom::levels();
+        let rom = test_rom::rom();
+
+        let asm = rom.export_asm(&levels, &[]).unwrap();
+
+        assert!(asm.contains("org $00C1CE : db $A9, $10 ; was $A9, $02"));
+        assert!(asm.contains("org $108000\nlevel_table:"));
+        for level in &levels {
+            assert!(asm.contains(&format!("\n{}:", arena_label(level))));
+        }
+    }
+
+    #[test]
+    fn smash_tv_asm_matches_loader_patch() {
+        let mut asm = String::from("lorom\n");
+        loader_patch_asm(&mut asm).unwrap();
+
+        assert_eq!(include_str!("../smash_tv.asm"), asm);
+    }
+
+    #[test]
+    fn imports_exported_level_data() {
+        let mut levels = test_rom::levels();
+        levels[5].waves.truncate(1);
+        levels[40].name = charset::fit_name("Imported", false);
+        levels[41].name = charset::fit_name("Odd{2C} name", false);
+        let rom = test_rom::rom();
+
+        let asm = rom.export_asm(&levels, &[]).unwrap();
+        assert_eq!(rom.import_asm(&asm), Ok(levels.clone()));
+
+        // assembling gives the same image as saving
+        let mut saved = Rom{rom: rom.rom.clone()};
+        saved.write_level_data(&levels, SaveMode::Expand).unwrap();
+        assert!(rom.assemble(&asm).unwrap().rom == saved.rom);
+    }
+
+    #[test]
+    fn reports_asm_errors_with_line_numbers() {
+        let rom = test_rom::rom();
+
+        let error = |line, message: &str| Err(RomError::Asm{line, message: message.to_string()});
+
+        assert_eq!(rom.import_asm("lorom\n\ndb $01"), error(3, "Data comes before any org"));
+        assert_eq!(rom.import_asm("org $108000\n  dw missing"), error(2, "Unknown label missing"));
+        assert_eq!(rom.import_asm("org $108000 : db 256"), error(1, "256 doesn't fit in 1 byte(s)"));
+        assert_eq!(
+            rom.import_asm("org $108000\nlda #$10"),
+            error(2, "Unsupported statement \"lda #$10\", only org, db, dw, dl and labels are"),
+        );
+    }
+}
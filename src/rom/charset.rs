@@ -0,0 +1,139 @@
+pub const NAME_LENGTH: usize = 26;
+
+// the font maps its glyphs to ascii codes, anything else shows up as garbage
+pub fn char_table() -> Vec<(u8, char)> {
+    let mut table: Vec<(u8, char)> = " !#'.?".chars().map(|c| (c as u8, c)).collect();
+
+    table.extend(('0' ..= '9').map(|c| (c as u8, c)));
+    table.extend(('A' ..= 'Z').map(|c| (c as u8, c)));
+    table.extend(('a' ..= 'z').map(|c| (c as u8, c)));
+
+    table
+}
+
+pub fn to_char(byte: u8) -> Option<char> {
+    char_table().into_iter().find(|&(b, _)| b == byte).map(|(_, c)| c)
+}
+
+pub fn to_byte(c: char) -> Option<u8> {
+    char_table().into_iter().find(|&(_, ch)| ch == c).map(|(b, _)| b)
+}
+
+// bytes without a glyph are kept as {XX} so names save back unchanged
+pub fn decode(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| byte_to_string(b)).collect()
+}
+
+fn byte_to_string(byte: u8) -> String {
+    match to_char(byte) {
+        Some(c) => c.to_string(),
+        None => format!("{{{:02X}}}", byte),
+    }
+}
+
+// one entry per game character, Err for characters the font doesn't have
+fn game_chars(name: &str) -> Vec<Result<u8, char>> {
+    let mut game_chars = Vec::new();
+    let mut rest = name;
+
+    while let Some(c) = rest.chars().next() {
+        let escaped = rest.get(1 .. 3)
+        .filter(|hex| c == '{' && rest.get(3 .. 4) == Some("}") && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        if let Some(byte) = escaped {
+            game_chars.push(Ok(byte));
+            rest = &rest[4 ..];
+        } else {
+            game_chars.push(to_byte(c).ok_or(c));
+            rest = &rest[c.len_utf8() ..];
+        }
+    }
+
+    game_chars
+}
+
+pub fn encode(name: &str) -> [u8; NAME_LENGTH] {
+    let mut bytes = [b' '; NAME_LENGTH];
+
+    for (byte, c) in bytes.iter_mut().zip(game_chars(name)) {
+        *byte = c.unwrap_or(b' ');
+    }
+
+    bytes
+}
+
+pub fn unsupported_chars(name: &str) -> Vec<char> {
+    let mut chars = Vec::new();
+
+    for c in game_chars(name).into_iter().filter_map(Result::err) {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+
+    chars
+}
+
+pub fn fit_name(name: &str, center: bool) -> String {
+    let mut supported: Vec<u8> = game_chars(name).into_iter().filter_map(Result::ok).collect();
+
+    if center {
+        let start = supported.iter().position(|&b| b != b' ').unwrap_or(supported.len());
+        let end = supported.iter().rposition(|&b| b != b' ').map_or(start, |end| end + 1);
+        let trimmed = &supported[start .. end.min(start + NAME_LENGTH)];
+
+        // center to 27 chars to put the extra space from uneven names on the left side.
+        // most, but not all, default names follow this convention.
+        let left = (NAME_LENGTH + 1 - trimmed.len()) / 2;
+        let right = NAME_LENGTH - trimmed.len() - left;
+
+        format!("{}{}{}", " ".repeat(left), decode(trimmed), " ".repeat(right))
+    } else {
+        supported.resize(NAME_LENGTH, b' ');
+        decode(&supported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centers_with_extra_space_on_the_left() {
+        assert_eq!(fit_name("Arena 1", true), format!("{}Arena 1{}", " ".repeat(10), " ".repeat(9)));
+        assert_eq!(fit_name("  Tank trouble ", true), format!("{}Tank trouble{}", " ".repeat(7), " ".repeat(7)));
+    }
+
+    #[test]
+    fn fits_to_name_length() {
+        let long = "A".repeat(40);
+        assert_eq!(fit_name(&long, true), "A".repeat(NAME_LENGTH));
+        assert_eq!(fit_name(&long, false), "A".repeat(NAME_LENGTH));
+        assert_eq!(fit_name("Orbs!", false), format!("Orbs!{}", " ".repeat(21)));
+    }
+
+    #[test]
+    fn drops_unsupported_characters() {
+        assert_eq!(unsupported_chars("Café, olé"), vec!['é', ',']);
+        assert_eq!(fit_name("Café", false).trim_end(), "Caf");
+        assert_eq!(&encode("é!")[.. 3], b" ! ");
+    }
+
+    #[test]
+    fn keeps_unknown_bytes_through_a_round_trip() {
+        let mut bytes = [b' '; NAME_LENGTH];
+        bytes[.. 8].copy_from_slice(b"Hi, you\x01");
+
+        assert_eq!(decode(&bytes).trim_end(), "Hi{2C} you{01}");
+        assert_eq!(encode(&decode(&bytes)), bytes);
+        assert_eq!(encode(&fit_name(&decode(&bytes), false)), bytes);
+        assert!(unsupported_chars(&decode(&bytes)).is_empty());
+    }
+
+    #[test]
+    fn centers_names_with_unknown_bytes() {
+        assert_eq!(fit_name("{2C}{2C}", true), format!("{}{{2C}}{{2C}}{}", " ".repeat(12), " ".repeat(12)));
+        assert_eq!(unsupported_chars("{2C"), vec!['{']);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum EnemyType {
     Grunt,
     WallGunner,
@@ -21,6 +21,8 @@ pub enum EnemyType {
     Presents,
     QuestionMark,
     Babe,
+    // ids the game doesn't use, like 0 and 19, kept so hacks using them still open
+    Unknown(u8),
 }
 
 impl EnemyType {
@@ -50,8 +52,8 @@ impl EnemyType {
         ]
     }
 
-    pub fn from_u8(val: u8) -> Option<Self> {
-        Some(match val {
+    pub fn from_u8(val: u8) -> Self {
+        match val {
             1 => Self::Grunt,
             2 => Self::WallGunner,
             3 => Self::Worm,
@@ -75,8 +77,8 @@ impl EnemyType {
             21 => Self::QuestionMark,
             22 => Self::Babe,
 
-            _ => return None,
-        })
+            _ => Self::Unknown(val),
+        }
     }
 
     pub fn to_u8(&self) -> u8 {
@@ -103,6 +105,8 @@ impl EnemyType {
             Self::Presents => 20,
             Self::QuestionMark => 21,
             Self::Babe => 22,
+
+            Self::Unknown(val) => *val,
         }
     }
 
@@ -130,6 +134,23 @@ impl EnemyType {
             Self::Presents => "Presents",
             Self::QuestionMark => "Question mark",
             Self::Babe => "Babe",
+
+            Self::Unknown(val) => return format!("Unknown ({})", val),
         }.to_string()
     }
 }
+
+pub struct EnemyInfo {
+    pub boss: bool,
+    pub collectible: bool,
+}
+
+impl EnemyType {
+    pub fn info(&self) -> EnemyInfo {
+        match self {
+            Self::CobraDeath | Self::MutoidMan | Self::Scarface => EnemyInfo { boss: true, collectible: false },
+            Self::Presents | Self::QuestionMark | Self::Babe => EnemyInfo { boss: false, collectible: true },
+            _ => EnemyInfo { boss: false, collectible: false },
+        }
+    }
+}
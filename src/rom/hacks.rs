@@ -0,0 +1,176 @@
+use std::ops::RangeInclusive;
+
+use super::{address::SnesAddr, Rom, RomError};
+
+// replaces the operand of an immediate load, like lda #$03 for the starting lives
+pub struct Hack {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub address: SnesAddr,
+    pub opcode: u8,
+    pub original: u8,
+    pub range: RangeInclusive<u8>,
+    pub value_names: &'static [&'static str],
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct HackSetting {
+    pub enabled: bool,
+    pub value: u8,
+}
+
+// todo: add entries once their address and original operand are verified against the rom.
+// lives, continues and the default difficulty are the first candidates.
+pub const HACKS: &[Hack] = &[];
+
+impl Hack {
+    pub fn value_name(&self, value: u8) -> String {
+        match self.value_names.get(value.wrapping_sub(*self.range.start()) as usize) {
+            Some(name) => name.to_string(),
+            None => value.to_string(),
+        }
+    }
+
+    fn read_setting(&self, rom: &Rom) -> Option<HackSetting> {
+        match rom.read(self.address, 2) {
+            Ok(&[opcode, value]) if opcode == self.opcode && (value == self.original || self.range.contains(&value)) => {
+                Some(HackSetting{enabled: value != self.original, value})
+            }
+            _ => None,
+        }
+    }
+}
+
+pub fn saved_settings(settings: &[Option<HackSetting>]) -> Vec<HackSetting> {
+    settings.iter().map(|setting| setting.unwrap_or(HackSetting{enabled: false, value: 0})).collect()
+}
+
+impl Rom {
+    pub fn hack_settings(&self) -> Vec<Option<HackSetting>> {
+        HACKS.iter().map(|hack| hack.read_setting(self)).collect()
+    }
+
+    pub fn apply_hacks(&mut self, settings: &[HackSetting]) -> Result<(), RomError> {
+        self.apply_hack_catalogue(HACKS, settings)
+    }
+
+    fn apply_hack_catalogue(&mut self, catalogue: &[Hack], settings: &[HackSetting]) -> Result<(), RomError> {
+        for (hack, value) in self.hack_writes(catalogue, settings)? {
+            self.write(hack.address + 1, &[value])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn hack_writes<'a>(&self, catalogue: &'a [Hack], settings: &[HackSetting]) -> Result<Vec<(&'a Hack, u8)>, RomError> {
+        let mut writes = Vec::new();
+
+        for (hack, setting) in catalogue.iter().zip(settings) {
+            let current = hack.read_setting(self);
+            let unchanged = match current {
+                // the value of a disabled hack doesn't matter
+                Some(current) => current == *setting || !(current.enabled || setting.enabled),
+                None => !setting.enabled,
+            };
+
+            if unchanged {
+                continue;
+            }
+            if current.is_none() {
+                return Err(RomError::PatchConflict{patch: hack.name, address: hack.address});
+            }
+
+            writes.push((hack, if setting.enabled { setting.value } else { hack.original }));
+        }
+
+        Ok(writes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_rom;
+
+    const TEST_HACKS: [Hack; 2] = [
+        Hack {
+            name: "Lives",
+            description: "",
+            address: SnesAddr(0x00D800),
+            opcode: 0xA9,
+            original: 3,
+            range: 1 ..= 9,
+            value_names: &[],
+        },
+        Hack {
+            name: "Difficulty",
+            description: "",
+            address: SnesAddr(0x00D810),
+            opcode: 0xA9,
+            original: 1,
+            range: 0 ..= 2,
+            value_names: &["Easy", "Normal", "Hard"],
+        },
+    ];
+
+    fn hack_rom() -> Rom {
+        let mut rom = test_rom::rom();
+        for hack in &TEST_HACKS {
+            rom.write(hack.address, &[hack.opcode, hack.original]).unwrap();
+        }
+        rom
+    }
+
+    fn settings(rom: &Rom) -> Vec<Option<HackSetting>> {
+        TEST_HACKS.iter().map(|hack| hack.read_setting(rom)).collect()
+    }
+
+    #[test]
+    fn round_trips_hack_settings() {
+        let mut rom = hack_rom();
+        assert_eq!(settings(&rom), [Some(HackSetting{enabled: false, value: 3}), Some(HackSetting{enabled: false, value: 1})]);
+
+        let changed = [HackSetting{enabled: true, value: 9}, HackSetting{enabled: true, value: 2}];
+        rom.apply_hack_catalogue(&TEST_HACKS, &changed).unwrap();
+        assert_eq!(settings(&rom), changed.map(Some));
+        assert_eq!(TEST_HACKS[1].value_name(2), "Hard");
+
+        let disabled = [HackSetting{enabled: false, value: 9}, HackSetting{enabled: true, value: 2}];
+        rom.apply_hack_catalogue(&TEST_HACKS, &disabled).unwrap();
+        assert_eq!(settings(&rom), [Some(HackSetting{enabled: false, value: 3}), Some(changed[1])]);
+    }
+
+    #[test]
+    fn leaves_untouched_hacks_alone() {
+        let mut rom = hack_rom();
+        let before = rom.rom.clone();
+
+        // a disabled hack keeps whatever the rom has
+        let untouched = [HackSetting{enabled: false, value: 0}, HackSetting{enabled: false, value: 0}];
+        assert!(rom.hack_writes(&TEST_HACKS, &untouched).unwrap().is_empty());
+
+        let loaded: Vec<_> = settings(&rom).into_iter().map(Option::unwrap).collect();
+        rom.apply_hack_catalogue(&TEST_HACKS, &loaded).unwrap();
+        assert_eq!(rom.rom, before);
+    }
+
+    #[test]
+    fn refuses_hack_on_unknown_code() {
+        let mut rom = hack_rom();
+        rom.write(TEST_HACKS[0].address, &[0xEA]).unwrap(); // nop
+        rom.write(TEST_HACKS[1].address + 1, &[0x40]).unwrap(); // operand out of range
+        let before = rom.rom.clone();
+
+        assert_eq!(settings(&rom), [None, None]);
+
+        // disabled, so nothing is written
+        let disabled = [HackSetting{enabled: false, value: 5}, HackSetting{enabled: false, value: 0}];
+        rom.apply_hack_catalogue(&TEST_HACKS, &disabled).unwrap();
+        assert_eq!(rom.rom, before);
+
+        let error = RomError::PatchConflict{patch: TEST_HACKS[1].name, address: TEST_HACKS[1].address};
+        let enabled = [HackSetting{enabled: false, value: 5}, HackSetting{enabled: true, value: 2}];
+        assert_eq!(rom.apply_hack_catalogue(&TEST_HACKS, &enabled), Err(error));
+        assert_eq!(rom.rom, before);
+    }
+}
@@ -0,0 +1,177 @@
+use std::ops::RangeInclusive;
+
+use super::{charset, LevelData};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Field {
+    Name,
+    WavesRemaining,
+    Enemy,
+    Count,
+    SpawnLimit,
+    CooldownTimer,
+    PreSpawned,
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub wave: Option<usize>,
+    pub field: Field,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, wave: Option<usize>, field: Field, message: String) -> Self {
+        Self { severity, wave, field, message }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct WaveRanges {
+    count: RangeInclusive<u16>,
+    spawn_limit: RangeInclusive<u8>,
+    pre_spawned: RangeInclusive<u8>,
+}
+
+// what each enemy type's waves look like in the rom as it was loaded, by enemy id
+#[derive(Default)]
+pub struct EnemyRanges(Vec<Option<WaveRanges>>);
+
+impl EnemyRanges {
+    pub fn new(level_data: &[LevelData]) -> Self {
+        let mut ranges: Vec<Option<WaveRanges>> = vec![None; 256];
+
+        for wave in level_data.iter().flat_map(|level| &level.waves) {
+            let range = &mut ranges[wave.enemy.to_u8() as usize];
+
+            *range = Some(match range.take() {
+                None => WaveRanges {
+                    count: wave.count ..= wave.count,
+                    spawn_limit: wave.spawn_limit ..= wave.spawn_limit,
+                    pre_spawned: wave.pre_spawned ..= wave.pre_spawned,
+                },
+                Some(r) => WaveRanges {
+                    count: wave.count.min(*r.count.start()) ..= wave.count.max(*r.count.end()),
+                    spawn_limit: wave.spawn_limit.min(*r.spawn_limit.start()) ..= wave.spawn_limit.max(*r.spawn_limit.end()),
+                    pre_spawned: wave.pre_spawned.min(*r.pre_spawned.start()) ..= wave.pre_spawned.max(*r.pre_spawned.end()),
+                },
+            });
+        }
+
+        Self(ranges)
+    }
+}
+
+pub fn lint(level: &LevelData, ranges: &EnemyRanges) -> Vec<Diagnostic> {
+    use Severity::{Error, Warning};
+
+    let mut diagnostics = Vec::new();
+
+    let unsupported = charset::unsupported_chars(&level.name);
+    if !unsupported.is_empty() {
+        diagnostics.push(Diagnostic::new(Warning, None, Field::Name, format!(
+            "Name contains characters not in the game's font: {}", unsupported.iter().collect::<String>()
+        )));
+    }
+
+    if level.waves_remaining as usize >= level.waves.len() {
+        diagnostics.push(Diagnostic::new(Error, None, Field::WavesRemaining, format!(
+            "Waves allowed remaining ({}) is not less than the wave count ({}), the arena is beaten immediately.",
+            level.waves_remaining, level.waves.len()
+        )));
+    }
+
+    let mut boss_seen = false;
+
+    for (idx, wave) in level.waves.iter().enumerate() {
+        let info = wave.enemy.info();
+        let range = ranges.0.get(wave.enemy.to_u8() as usize).cloned().flatten();
+        let name = wave.enemy.name();
+        let wave_idx = Some(idx);
+
+        if wave.count == 0 {
+            diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::Count, "Count is 0, nothing spawns.".to_string()));
+        } else if let Some(count) = range.as_ref().map(|r| &r.count).filter(|count| !count.contains(&wave.count)) {
+            diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::Count, format!(
+                "{} count is {}-{} in the rom as loaded.", name, count.start(), count.end()
+            )));
+        }
+
+        if wave.spawn_limit as u16 > wave.count {
+            diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::SpawnLimit, format!(
+                "Spawn limit ({}) is higher than the count ({}).", wave.spawn_limit, wave.count
+            )));
+        } else if let Some(spawn_limit) = range.as_ref().map(|r| &r.spawn_limit).filter(|limit| !limit.contains(&wave.spawn_limit)) {
+            diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::SpawnLimit, format!(
+                "{} spawn limit is {}-{} in the rom as loaded.", name, spawn_limit.start(), spawn_limit.end()
+            )));
+        }
+
+        if wave.cooldown_timer == 0 && wave.count > wave.pre_spawned as u16 {
+            diagnostics.push(Diagnostic::new(Error, wave_idx, Field::CooldownTimer, format!(
+                "Cooldown timer is 0 but {} enemies are left to spawn, they never will.",
+                wave.count - wave.pre_spawned as u16
+            )));
+        }
+
+        if let Some(pre_spawned) = range.as_ref().map(|r| &r.pre_spawned).filter(|pre| !pre.contains(&wave.pre_spawned)) {
+            diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::PreSpawned, format!(
+                "{} pre-spawned is {}-{} in the rom as loaded.", name, pre_spawned.start(), pre_spawned.end()
+            )));
+        }
+
+        if info.boss {
+            if boss_seen {
+                diagnostics.push(Diagnostic::new(Warning, wave_idx, Field::Enemy, "More than one boss in this arena.".to_string()));
+            }
+            boss_seen = true;
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| std::cmp::Reverse(diagnostic.severity));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::{enemy_type::EnemyType, test_rom};
+
+    fn warnings(level: &LevelData, ranges: &EnemyRanges, field: Field) -> usize {
+        lint(level, ranges).iter().filter(|diagnostic| diagnostic.field == field).count()
+    }
+
+    #[test]
+    fn warns_outside_the_loaded_ranges() {
+        let levels = test_rom::levels();
+        let ranges = EnemyRanges::new(&levels);
+
+        let mut level = levels[0].clone();
+        assert!(lint(&level, &ranges).is_empty());
+
+        level.waves[0].count = 500;
+        level.waves[0].pre_spawned = 200;
+        assert_eq!(warnings(&level, &ranges, Field::Count), 1);
+        assert_eq!(warnings(&level, &ranges, Field::PreSpawned), 1);
+
+        // no waves to compare against
+        assert_eq!(warnings(&level, &EnemyRanges::default(), Field::Count), 0);
+    }
+
+    #[test]
+    fn warns_about_a_second_boss() {
+        let levels = test_rom::levels();
+        let mut level = levels[1].clone();
+        level.waves[0].enemy = EnemyType::MutoidMan;
+        level.waves[1].enemy = EnemyType::Scarface;
+
+        assert_eq!(warnings(&level, &EnemyRanges::default(), Field::Enemy), 1);
+    }
+}
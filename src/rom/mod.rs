@@ -1,7 +1,23 @@
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use self::address::{LoRom, Mapper, SnesAddr};
 use self::enemy_type::EnemyType;
+use self::hacks::HackSetting;
 
+pub mod address;
+pub mod asm;
+pub mod charset;
 pub mod enemy_type;
+pub mod hacks;
+pub mod lint;
+pub mod patch;
+pub mod symbols;
+pub mod usage;
 
+#[derive(PartialEq, Clone, Debug)]
 pub struct Wave {
     pub enemy: EnemyType,
     pub count: u16,
@@ -12,6 +28,7 @@ pub struct Wave {
     pub spawn_timer: u16,
 }
 
+#[derive(PartialEq, Clone, Debug)]
 pub struct LevelData {
     pub circuit: u8,
     pub arena: u8,
@@ -21,100 +38,287 @@ pub struct LevelData {
     pub connections: [u8; 3],
 }
 
+pub const VANILLA_LEVEL_OFFSET: SnesAddr = SnesAddr(0x02B5F0);
+const NAMES: SnesAddr = SnesAddr(0x00E977 + 3 * 2);
+pub const EXPANDED_LEVEL_OFFSET: SnesAddr = SnesAddr(0x108000);
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum SaveMode {
+    #[default]
+    InPlace,
+    Expand,
+}
+
+impl LevelData {
+    fn mismatch(&self, other: &LevelData) -> Option<String> {
+        if (self.circuit, self.arena) != (other.circuit, other.arena) {
+            return Some(format!("arena number ({}, {} vs {}, {})", self.circuit, self.arena, other.circuit, other.arena));
+        }
+
+        if charset::encode(&self.name) != charset::encode(&other.name) {
+            return Some(format!("name (\"{}\" vs \"{}\")", self.name.trim(), other.name.trim()));
+        }
+
+        if self.waves_remaining != other.waves_remaining {
+            return Some(format!("waves allowed remaining ({} vs {})", self.waves_remaining, other.waves_remaining));
+        }
+
+        if self.connections != other.connections {
+            return Some(format!("connections ({:?} vs {:?})", self.connections, other.connections));
+        }
+
+        if self.waves.len() != other.waves.len() {
+            return Some(format!("wave count ({} vs {})", self.waves.len(), other.waves.len()));
+        }
+
+        self.waves.iter().zip(&other.waves).position(|(a, b)| a != b)
+        .map(|idx| format!("wave {}", idx + 1))
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Rom {
     pub rom: Vec<u8>,
 }
 
-impl Rom {
-    pub fn get_level_data(&self) -> Vec<LevelData> {
+#[derive(PartialEq, Debug)]
+pub enum RomError {
+    OutOfBounds(usize),
+    MalformedAddress(SnesAddr),
+    ArenaCount(usize),
+    WaveCount { arena: usize, count: usize },
+    LevelDataTooLarge(usize),
+    AlreadyVanilla,
+    DoesNotFit,
+    Mismatch(String),
+    PatchConflict { patch: &'static str, address: SnesAddr },
+    Asm { line: usize, message: String },
+}
 
-        let level_offset = if self.rom[Self::from_snes_address(0x00C1CF)] == 0x02 { // load from default location
-            0x02B5F0
-        } else { // load from bank 0x10
-            0x108000
-        };
+impl std::fmt::Display for RomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds(offset) => write!(f, "Reached the end of the rom at 0x{:06X}", offset),
+            Self::MalformedAddress(address) => write!(f, "Found a pointer to {}, which isn't in rom", address),
+            Self::ArenaCount(count) => write!(f, "Expected 52 arenas, got {}", count),
+            Self::WaveCount{arena, count} => write!(
+                f, "{} has {} waves, it needs 1 to 256", circuit_arena_name().get(*arena).unwrap_or(&"An arena"), count
+            ),
+            Self::LevelDataTooLarge(size) => write!(f, "Level data is too large to fit in one bank ({} bytes)", size),
+            Self::AlreadyVanilla => write!(f, "The rom already uses the original layout"),
+            Self::DoesNotFit => write!(f, "Level data doesn't fit in its original location"),
+            Self::Mismatch(mismatch) => write!(f, "{}", mismatch),
+            Self::PatchConflict{patch, address} => write!(
+                f, "Can't apply \"{}\", the code at {} was changed by another hack", patch, address
+            ),
+            Self::Asm{line, message} => write!(f, "Line {}: {}", line, message),
+        }
+    }
+}
 
+impl Rom {
+    pub fn get_level_data(&self) -> Result<Vec<LevelData>, RomError> {
+        let level_offset = self.level_offset()?;
         let mut levels = Vec::new();
 
         let circuit_arena_counts = [11, 18, 23];
         for (circuit, &arena_max) in circuit_arena_counts.iter().enumerate() {
             for arena in 1 ..= arena_max {
-                let base = self.arena_offset(circuit as u8, arena, level_offset);
-
-                let wave_count = self.rom[base] as usize + 1;
-
-                let mut waves = Vec::new();
-    
-                for w in 0 .. wave_count {
-                    let enemy_type = match EnemyType::from_u8(self.rom[base + 1 + w * 10]) {
-                        Some(enemy) => enemy,
-                        None => {
-                            println!("unknown enemy: {:02X} at {:X}", self.rom[base + 1 + w * 10], base);
-                            println!("{}, {}", circuit, arena);
-                            todo!()
-                        }
-                    };
-        
-                    waves.push(Wave {
-                        enemy: enemy_type,
-                        count: u16::from_le_bytes([self.rom[base + 2 + w * 10], self.rom[base + 3 + w * 10]]),
-                        spawn_limit: self.rom[base + 4 + w * 10],
-                        unknown: self.rom[base + 5 + w * 10],
-                        cooldown_timer: u16::from_le_bytes([self.rom[base + 6 + w * 10], self.rom[base + 7 + w * 10]]),
-                        pre_spawned: self.rom[base + 8 + w * 10],
-                        spawn_timer: u16::from_le_bytes([self.rom[base + 9 + w * 10], self.rom[base + 10 + w * 10]]),
-                    });
-                }
-    
-                let waves_remaining = self.rom[base + 1 + wave_count * 10];
-    
-                let circuit_connection_list_address = Self::from_snes_address(0x00AA66 + circuit as u32);
-                let circuit_connection_list = Self::from_snes_address(u32::from_le_bytes([
-                    self.rom[circuit_connection_list_address],
-                    self.rom[circuit_connection_list_address + 3],
-                    0,
-                    0,
-                ]));
-        
-                let connections = [
-                    self.rom[circuit_connection_list + arena as usize * 3],
-                    self.rom[circuit_connection_list + arena as usize * 3 + 1],
-                    self.rom[circuit_connection_list + arena as usize * 3 + 2],
-                ];
-
-                let circuit_names_offset = Self::from_snes_address(0x00E977 + circuit as u32 * 2);
-                let name_offset = Self::from_snes_address(u32::from_le_bytes([
-                    self.rom[circuit_names_offset],
-                    self.rom[circuit_names_offset+1],
-                    0,
-                    0,
-                ]));
-        
-                let offset = name_offset + (arena - 1) as usize * 26;
-                let s = std::str::from_utf8(&self.rom[offset .. offset + 26]).expect("invalid utf-8 sequence");
+                let base = self.arena_offset(circuit as u8, arena, level_offset)?;
+
+                let wave_count = self.read_u8(base)? as usize + 1;
+                let wave_data = self.read(base + 1, wave_count * 10 + 1)?;
+
+                let waves = wave_data.chunks_exact(10).map(|wave| Wave {
+                    enemy: EnemyType::from_u8(wave[0]),
+                    count: u16::from_le_bytes([wave[1], wave[2]]),
+                    spawn_limit: wave[3],
+                    unknown: wave[4],
+                    cooldown_timer: u16::from_le_bytes([wave[5], wave[6]]),
+                    pre_spawned: wave[7],
+                    spawn_timer: u16::from_le_bytes([wave[8], wave[9]]),
+                }).collect();
+
+                let waves_remaining = wave_data[wave_count * 10];
+
+                let connection_list = self.connection_list(circuit as u8)?;
+                let connections = self.read(connection_list + arena as usize * 3, 3)?;
+
+                let offset = self.name_list(circuit as u8)? + (arena - 1) as usize * charset::NAME_LENGTH;
+                let name = charset::decode(self.read(offset, charset::NAME_LENGTH)?);
 
                 levels.push(LevelData {
                     circuit: circuit as u8,
-                    arena: arena,
-                    name: s.to_string(),
-                    waves: waves,
-                    waves_remaining: waves_remaining,
-                    connections: connections,
+                    arena,
+                    name,
+                    waves,
+                    waves_remaining,
+                    connections: [connections[0], connections[1], connections[2]],
                 });
             }
         }
 
-        levels
+        Ok(levels)
+    }
+
+    // super bootleg rom check
+    pub fn is_smash_tv(rom: &[u8]) -> bool {
+        rom.starts_with(&[0x78, 0x9C, 0x00])
+    }
+
+    pub fn with_level_data(&self, level_data: &[LevelData], mode: SaveMode, hacks: &[HackSetting]) -> Result<Rom, RomError> {
+        let mut rom = Rom{rom: self.rom.clone()};
+        rom.write_level_data(level_data, mode)?;
+        rom.apply_hacks(hacks)?;
+        rom.verify_level_data(level_data)?;
+
+        Ok(rom)
     }
 
-    pub fn save_level_data(&mut self, level_data: &[LevelData]) {
+    pub fn verify_level_data(&self, expected: &[LevelData]) -> Result<(), RomError> {
+        let saved = self.get_level_data()?;
+
+        if saved.len() != expected.len() {
+            return Err(RomError::Mismatch(format!("Expected {} arenas, found {}", expected.len(), saved.len())));
+        }
+
+        for (idx, (expected, saved)) in expected.iter().zip(&saved).enumerate() {
+            if let Some(field) = expected.mismatch(saved) {
+                return Err(RomError::Mismatch(format!("{} doesn't match after saving: {}", circuit_arena_name()[idx], field)));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save_rom(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, &self.rom)
+    }
+
+    pub fn write_level_data(&mut self, level_data: &[LevelData], mode: SaveMode) -> Result<(), RomError> {
         if level_data.len() != 52 {
-            println!("not supported atm");
-            return;
+            return Err(RomError::ArenaCount(level_data.len()));
+        }
+
+        if let Some(arena) = level_data.iter().position(|level| !(1 ..= 256).contains(&level.waves.len())) {
+            return Err(RomError::WaveCount{arena, count: level_data[arena].waves.len()});
+        }
+
+        let in_place = mode == SaveMode::InPlace
+            && self.level_offset()? == VANILLA_LEVEL_OFFSET
+            && self.fits_in_place(level_data)?;
+
+        let (level_offset, padding) = if in_place {
+            // keep the end of the table where it was, or the space freed by shrinking is lost to the next save
+            (VANILLA_LEVEL_OFFSET, self.in_place_space()? - Self::level_table_size(level_data))
+        } else {
+            let level_table_size = Self::level_table_size(level_data);
+            if level_table_size > Self::bank_space(EXPANDED_LEVEL_OFFSET) {
+                return Err(RomError::LevelDataTooLarge(level_table_size));
+            }
+
+            self.modify_rom()?;
+            (EXPANDED_LEVEL_OFFSET, 0)
+        };
+
+        let level_table = Self::serialize_level_table(level_data, level_offset, padding);
+        self.write(level_offset, &level_table)?;
+
+        // save connections
+        for level in level_data {
+            let connection_list = self.connection_list(level.circuit)?;
+            self.write(connection_list + level.arena as usize * 3, &level.connections)?;
+        }
+
+        // save arena names
+        for (idx, level) in level_data.iter().enumerate() {
+            self.write(NAMES + idx * charset::NAME_LENGTH, &charset::encode(&level.name))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn restore_vanilla_layout(&mut self, level_data: &[LevelData]) -> Result<(), RomError> {
+        if self.level_offset()? == VANILLA_LEVEL_OFFSET {
+            return Err(RomError::AlreadyVanilla);
+        }
+
+        if !self.fits_in_place(level_data)? {
+            return Err(RomError::DoesNotFit);
+        }
+
+        let mut rom = Rom{rom: self.rom.clone()};
+        rom.unmodify_rom()?;
+        rom.write_level_data(level_data, SaveMode::InPlace)?;
+        rom.verify_level_data(level_data)?;
+
+        *self = rom;
+        Ok(())
+    }
+
+    fn modify_rom(&mut self) -> Result<(), RomError> {
+        self.apply_patch(&patch::LEVEL_LOADER)?;
+
+        if self.rom.len() < 0x100000 {
+            self.rom.resize(0x100000, 0);
+        }
+        Ok(())
+    }
+
+    fn unmodify_rom(&mut self) -> Result<(), RomError> {
+        self.revert_patch(&patch::LEVEL_LOADER)?;
+
+        self.rom.truncate(0x80000);
+        Ok(())
+    }
+
+    fn level_offset(&self) -> Result<SnesAddr, RomError> {
+        Ok(if self.read_u8(SnesAddr(0x00C1CF))? == 0x02 { // load from default location
+            VANILLA_LEVEL_OFFSET
+        } else { // load from bank 0x10
+            EXPANDED_LEVEL_OFFSET
+        })
+    }
+
+    // from the start of the pointer tables to the end of the last arena
+    fn level_data_size(&self, base_offset: SnesAddr) -> Result<usize, RomError> {
+        let mut end = base_offset;
+
+        let circuit_arena_counts = [11, 18, 23];
+        for (circuit, &arena_max) in circuit_arena_counts.iter().enumerate() {
+            for arena in 1 ..= arena_max {
+                let base = self.arena_offset(circuit as u8, arena, base_offset)?;
+                let wave_count = self.read_u8(base)? as usize + 1;
+
+                end = end.max(base + 1 + wave_count * 10 + 1);
+            }
         }
 
-        self.modify_rom();
+        Ok((end.0 - base_offset.0) as usize)
+    }
+
+    fn in_place_space(&self) -> Result<usize, RomError> {
+        Ok(self.level_data_size(VANILLA_LEVEL_OFFSET)?.min(Self::bank_space(VANILLA_LEVEL_OFFSET)))
+    }
+
+    fn fits_in_place(&self, level_data: &[LevelData]) -> Result<bool, RomError> {
+        Ok(Self::level_table_size(level_data) <= self.in_place_space()?)
+    }
+
+    fn bank_space(offset: SnesAddr) -> usize {
+        0x10000 - offset.offset() as usize
+    }
+
+    fn level_table_size(level_data: &[LevelData]) -> usize {
+        let pointers = (3 + 3 + level_data.len()) * 2;
+        let arenas: usize = level_data.iter().map(|level| 1 + level.waves.len() * 10 + 1).sum();
+
+        pointers + arenas
+    }
+
+    // padding goes between the pointers and the arenas
+    fn serialize_level_table(level_data: &[LevelData], base_offset: SnesAddr, padding: usize) -> Vec<u8> {
+        let base = base_offset.offset();
 
         let mut circuit_offsets = Vec::new();
         let mut arena_offsets = Vec::new();
@@ -125,7 +329,7 @@ impl Rom {
         for level in level_data {
             if current_circuit != level.circuit {
                 // store offset to new circuit
-                circuit_offsets.push((3 + arena_offsets.len()) as u16 * 2 | 0x8000);
+                circuit_offsets.push(base + (3 + arena_offsets.len()) as u16 * 2);
 
                 // insert a 0x0000 (tv studio) into the list at new circuits
                 arena_offsets.push(0);
@@ -134,59 +338,23 @@ impl Rom {
             }
 
             // store offset to current arena
-            let offset = ((3 + 3 + level_data.len()) * 2) + arena_data.len();
-            arena_offsets.push(offset as u16 | 0x8000);
+            let offset = ((3 + 3 + level_data.len()) * 2) + padding + arena_data.len();
+            arena_offsets.push(base + offset as u16);
 
             arena_data.extend_from_slice(&Self::serialize_level_data(level));
         }
 
         circuit_offsets.append(&mut arena_offsets);
-        let (_, body, _) = unsafe { circuit_offsets.align_to::<u8>() };
-        let mut circuit_arena_offsets_u8 = body.to_vec();
-        circuit_arena_offsets_u8.append(&mut arena_data);
+        let mut level_table: Vec<u8> = circuit_offsets.iter().flat_map(|offset| offset.to_le_bytes()).collect();
+        level_table.resize(level_table.len() + padding, 0);
+        level_table.append(&mut arena_data);
 
-        let offset = Self::from_snes_address(0x108000);
-        self.rom[offset .. offset + circuit_arena_offsets_u8.len()].clone_from_slice(&circuit_arena_offsets_u8);
-
-        // save connections
-        for level in level_data {
-            let circuit_connection_list_address = Self::from_snes_address(0x00AA66 + level.circuit as u32);
-            let circuit_connection_list = Self::from_snes_address(u32::from_le_bytes([
-                self.rom[circuit_connection_list_address],
-                self.rom[circuit_connection_list_address + 3],
-                0,
-                0,
-            ]));
-
-            for (idx, &con) in level.connections.iter().enumerate() {
-                self.rom[circuit_connection_list + level.arena as usize * 3 + idx] = con;
-            }
-        }
-
-        // save arena names
-        for (idx, level) in level_data.iter().enumerate() {
-            let base = Self::from_snes_address(0x00E977 + 3 * 2);
-            self.rom[base + idx * 26 .. base + idx * 26 + 26].copy_from_slice(level.name.as_bytes());
-        }
-
-        std::fs::write("Smash TV edit.sfc", &self.rom).expect("Couldn't save new rom");
-    }
-
-    fn modify_rom(&mut self) {
-        self.rom[Self::from_snes_address(0x00FFD7)] = 0x0A;
-        self.rom[Self::from_snes_address(0x00C1CF)] = 0x10;
-
-        self.rom[Self::from_snes_address(0x00C1D8)] = 0x00;
-        self.rom[Self::from_snes_address(0x00C1D9)] = 0x80;
-        self.rom[Self::from_snes_address(0x00C1DD)] = 0x01;
-        self.rom[Self::from_snes_address(0x00C1DE)] = 0x80;
-
-        self.rom.resize(0x100000, 0);
+        level_table
     }
 
     fn serialize_level_data(level_data: &LevelData) -> Vec<u8> {
         let mut serial = Vec::new();
-        serial.push(level_data.waves.len() as u8 - 1);
+        serial.push((level_data.waves.len() - 1) as u8);
 
         for wave in &level_data.waves {
             serial.push(wave.enemy.to_u8());
@@ -203,38 +371,73 @@ impl Rom {
         serial
     }
 
-    fn arena_offset(&self, circuit: u8, arena: u8, base_offset: u32) -> usize {
-        let circuit_offset = Self::from_snes_address(base_offset + circuit as u32 * 2);
+    fn arena_offset(&self, circuit: u8, arena: u8, base_offset: SnesAddr) -> Result<SnesAddr, RomError> {
+        let arena_list = self.read_ptr16(base_offset + circuit as usize * 2, base_offset.bank())?;
+        self.read_ptr16(arena_list + arena as usize * 2, base_offset.bank())
+    }
 
-        let arena_base = Self::from_snes_address(u32::from_le_bytes([
-            self.rom[circuit_offset],
-            self.rom[circuit_offset + 1],
-            base_offset.to_le_bytes()[2],
-            0,
-        ]));
+    // connection lists are pointed to by a table of low bytes followed by one of high bytes
+    fn connection_list(&self, circuit: u8) -> Result<SnesAddr, RomError> {
+        let pointers = SnesAddr(0x00AA66) + circuit as usize;
+        Ok(SnesAddr::new(0, u16::from_le_bytes([self.read_u8(pointers)?, self.read_u8(pointers + 3)?])))
+    }
 
-        let arena_offset = arena_base + arena as usize * 2;
+    fn name_list(&self, circuit: u8) -> Result<SnesAddr, RomError> {
+        self.read_ptr16(SnesAddr(0x00E977) + circuit as usize * 2, 0)
+    }
 
-        let base = Self::from_snes_address(u32::from_le_bytes([
-            self.rom[arena_offset],
-            self.rom[arena_offset + 1],
-            base_offset.to_le_bytes()[2],
-            0,
-        ]));
+    fn mapper(&self) -> &dyn Mapper {
+        &LoRom
+    }
 
-        base
+    pub fn file_offset(&self, address: SnesAddr) -> Result<usize, RomError> {
+        self.mapper().to_file_offset(address).ok_or(RomError::MalformedAddress(address))
     }
 
-    fn from_snes_address(address: u32) -> usize {
-        if address & 0x8000 == 0 {
-            println!("warning: malformed address: 0x{:6X}", address);
-        }
+    pub fn read(&self, address: SnesAddr, len: usize) -> Result<&[u8], RomError> {
+        let offset = self.file_offset(address)?;
+        self.rom.get(offset .. offset + len).ok_or(RomError::OutOfBounds(offset))
+    }
+
+    pub fn read_u8(&self, address: SnesAddr) -> Result<u8, RomError> {
+        Ok(self.read(address, 1)?[0])
+    }
+
+    pub fn read_u16(&self, address: SnesAddr) -> Result<u16, RomError> {
+        let bytes = self.read(address, 2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
 
-        let bank = (address >> 16) * 0x8000;
-        let offset = (address - 0x8000) & 0xFFFF;
+    pub fn read_ptr16(&self, address: SnesAddr, bank: u8) -> Result<SnesAddr, RomError> {
+        Ok(SnesAddr::new(bank, self.read_u16(address)?))
+    }
 
-        (bank + offset) as usize
+    #[allow(dead_code)] // the level data only has short pointers
+    pub fn read_ptr24(&self, address: SnesAddr) -> Result<SnesAddr, RomError> {
+        let bytes = self.read(address, 3)?;
+        Ok(SnesAddr(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0])))
     }
+
+    fn write(&mut self, address: SnesAddr, bytes: &[u8]) -> Result<(), RomError> {
+        let offset = self.file_offset(address)?;
+
+        self.rom.get_mut(offset .. offset + bytes.len())
+        .ok_or(RomError::OutOfBounds(offset))?
+        .copy_from_slice(bytes);
+
+        Ok(())
+    }
+}
+
+// arenas edited since base win over the reloaded ones
+pub fn merge_level_data(base: &[LevelData], edited: &[LevelData], mut reloaded: Vec<LevelData>) -> Vec<LevelData> {
+    for ((base, edited), reloaded) in base.iter().zip(edited).zip(&mut reloaded) {
+        if base != edited {
+            *reloaded = edited.clone();
+        }
+    }
+
+    reloaded
 }
 
 pub fn circuit_arena_name() -> [&'static str; 52] {
@@ -295,3 +498,330 @@ pub fn circuit_arena_name() -> [&'static str; 52] {
         "Not enough keys!",
     ]
 }
+
+#[cfg(test)]
+pub mod test_rom;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::enemy_type::EnemyType;
+
+    fn test_rom() -> (Rom, Vec<LevelData>) {
+        (test_rom::rom(), test_rom::levels())
+    }
+
+    fn file_offset(address: u32) -> usize {
+        LoRom.to_file_offset(SnesAddr(address)).unwrap()
+    }
+
+    fn add_wave(level: &mut LevelData) {
+        let wave = level.waves[0].clone();
+        level.waves.push(wave);
+    }
+
+    #[test]
+    fn test_rom_passes_rom_check() {
+        let (rom, _) = test_rom();
+        assert!(Rom::is_smash_tv(&rom.rom));
+        assert!(!Rom::is_smash_tv(&[0x78, 0x9C]));
+    }
+
+    #[test]
+    fn reads_level_data() {
+        let (rom, levels) = test_rom();
+        assert_eq!(rom.get_level_data(), Ok(levels));
+    }
+
+    #[test]
+    fn saves_in_place_when_data_fits() {
+        let (mut rom, mut levels) = test_rom();
+        levels[5].waves[0].count = 99;
+        levels[5].name = charset::fit_name("Renamed", true);
+
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+
+        assert_eq!(rom.rom.len(), 0x80000);
+        assert_eq!(rom.level_offset(), Ok(VANILLA_LEVEL_OFFSET));
+        assert_eq!(rom.get_level_data(), Ok(levels));
+        assert_following_data_intact(&rom);
+    }
+
+    #[test]
+    fn shrinking_in_place_keeps_the_space() {
+        let (mut rom, mut levels) = test_rom();
+        let wave = levels[21].waves.pop().unwrap();
+
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+        assert_eq!(rom.get_level_data(), Ok(levels.clone()));
+
+        levels[21].waves.push(wave);
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+
+        assert_eq!(rom.rom.len(), 0x80000);
+        assert_eq!(rom.get_level_data(), Ok(levels));
+        assert_following_data_intact(&rom);
+    }
+
+    #[test]
+    fn restores_vanilla_layout_after_shrinking_in_place() {
+        let (mut rom, mut levels) = test_rom();
+        let wave = levels[21].waves.pop().unwrap();
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+
+        levels[21].waves.push(wave);
+        rom.write_level_data(&levels, SaveMode::Expand).unwrap();
+        rom.restore_vanilla_layout(&levels).unwrap();
+
+        assert_eq!(rom.rom.len(), 0x80000);
+        assert_eq!(rom.get_level_data(), Ok(levels));
+        assert_following_data_intact(&rom);
+    }
+
+    fn assert_following_data_intact(rom: &Rom) {
+        let end = file_offset(0x02B5F0) + Rom::level_table_size(&test_rom::levels());
+        assert_eq!(rom.rom[end .. end + 4], test_rom::FOLLOWING_DATA);
+    }
+
+    #[test]
+    fn expands_when_data_grows() {
+        let (mut rom, mut levels) = test_rom();
+        add_wave(&mut levels[20]);
+
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+
+        assert_eq!(rom.rom.len(), 0x100000);
+        assert_eq!(rom.level_offset(), Ok(EXPANDED_LEVEL_OFFSET));
+        assert_eq!(rom.get_level_data(), Ok(levels));
+    }
+
+    #[test]
+    fn expand_mode_always_expands() {
+        let (mut rom, levels) = test_rom();
+
+        rom.write_level_data(&levels, SaveMode::Expand).unwrap();
+
+        assert_eq!(rom.rom.len(), 0x100000);
+        assert_eq!(rom.read_u8(SnesAddr(0x00FFD7)), Ok(0x0A));
+        assert_eq!(rom.get_level_data(), Ok(levels));
+    }
+
+    fn assert_round_trip(levels: &[LevelData], mode: SaveMode) {
+        let (mut rom, _) = test_rom();
+        rom.write_level_data(levels, mode).unwrap();
+
+        let reloaded = rom.get_level_data().unwrap();
+        assert_eq!(reloaded, levels);
+
+        // saving what was loaded again must not change anything
+        let saved = rom.rom.clone();
+        rom.write_level_data(&reloaded, mode).unwrap();
+        assert_eq!(rom.rom, saved);
+    }
+
+    #[test]
+    fn round_trips_level_data() {
+        let (rom, _) = test_rom();
+        let levels = rom.get_level_data().unwrap();
+
+        assert_round_trip(&levels, SaveMode::InPlace);
+        assert_round_trip(&levels, SaveMode::Expand);
+    }
+
+    #[test]
+    fn round_trips_edited_level_data() {
+        let (_, mut levels) = test_rom();
+
+        // reorder waves, rename, reconnect and add waves across every circuit
+        levels[3].waves.reverse();
+        levels[12].name = charset::fit_name("Edited", false);
+        levels[30].connections = [0xFF, 2, 0];
+        levels[51].waves_remaining = 2;
+        add_wave(&mut levels[51]);
+        levels[51].waves[1].enemy = EnemyType::Unknown(19);
+
+        assert_round_trip(&levels, SaveMode::InPlace);
+        assert_round_trip(&levels, SaveMode::Expand);
+    }
+
+    #[test]
+    fn keeps_name_bytes_without_a_glyph() {
+        let (mut rom, _) = test_rom();
+        rom.write(NAMES + 3, b",\x01").unwrap();
+        let names = rom.read(NAMES, 52 * charset::NAME_LENGTH).unwrap().to_vec();
+
+        let levels = rom.get_level_data().unwrap();
+        rom.write_level_data(&levels, SaveMode::InPlace).unwrap();
+
+        assert_eq!(rom.read(NAMES, 52 * charset::NAME_LENGTH), Ok(&names[..]));
+    }
+
+    #[test]
+    fn merge_keeps_edits_and_reloaded_changes() {
+        let base = test_rom::levels();
+
+        let mut edited = base.clone();
+        edited[3].waves_remaining = 7;
+
+        let mut reloaded = base.clone();
+        reloaded[3].connections = [1, 2, 3];
+        reloaded[20].waves.pop();
+
+        let merged = merge_level_data(&base, &edited, reloaded.clone());
+        assert_eq!(merged[3], edited[3]);
+        assert_eq!(merged[20], reloaded[20]);
+        assert_eq!(merged[.. 3], base[.. 3]);
+    }
+
+    #[test]
+    fn verify_reports_mismatches() {
+        let (rom, mut levels) = test_rom();
+        assert_eq!(rom.verify_level_data(&levels), Ok(()));
+
+        // names are compared as stored, padding doesn't matter
+        levels[0].name = levels[0].name.trim_end().to_string();
+        assert_eq!(rom.verify_level_data(&levels), Ok(()));
+
+        levels[40].waves[0].spawn_timer += 1;
+        let err = rom.verify_level_data(&levels).unwrap_err().to_string();
+        assert!(err.starts_with(circuit_arena_name()[40]));
+        assert!(err.ends_with("wave 1"));
+    }
+
+    #[test]
+    fn restores_vanilla_layout() {
+        let (mut rom, levels) = test_rom();
+        let original = rom.rom.clone();
+
+        rom.write_level_data(&levels, SaveMode::Expand).unwrap();
+        rom.restore_vanilla_layout(&levels).unwrap();
+
+        assert_eq!(rom.rom.len(), original.len());
+        assert_eq!(rom.patch_state(&patch::LEVEL_LOADER), Ok(patch::PatchState::Original));
+        assert_eq!(rom.get_level_data(), Ok(levels));
+        assert_following_data_intact(&rom);
+    }
+
+    #[test]
+    fn restore_refuses_data_that_does_not_fit() {
+        let (mut rom, mut levels) = test_rom();
+        add_wave(&mut levels[0]);
+
+        rom.write_level_data(&levels, SaveMode::Expand).unwrap();
+        let expanded = rom.rom.clone();
+
+        assert_eq!(rom.restore_vanilla_layout(&levels), Err(RomError::DoesNotFit));
+        assert_eq!(rom.rom, expanded);
+    }
+
+    // xorshift64
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, max: usize) -> usize {
+            (self.next() % max as u64) as usize
+        }
+    }
+
+    // errors are fine, panics are not
+    fn exercise(mut rom: Rom, rng: &mut Rng) {
+        if let Ok(levels) = rom.get_level_data() {
+            let mode = if rng.below(2) == 0 { SaveMode::InPlace } else { SaveMode::Expand };
+            let _ = rom.write_level_data(&levels, mode);
+            let _ = rom.verify_level_data(&levels);
+            let _ = rom.restore_vanilla_layout(&levels);
+        }
+    }
+
+    #[test]
+    fn parses_random_images_without_panicking() {
+        let mut rng = Rng(0x5EED);
+
+        for _ in 0 .. 40 {
+            let len = rng.below(0x90000);
+            let mut image: Vec<u8> = (0 .. len).map(|_| rng.next() as u8).collect();
+
+            // get past the rom check half the time
+            if len >= 3 && rng.below(2) == 0 {
+                image[.. 3].copy_from_slice(&[0x78, 0x9C, 0x00]);
+            }
+
+            exercise(Rom{rom: image}, &mut rng);
+        }
+    }
+
+    #[test]
+    fn parses_mutated_images_without_panicking() {
+        let mut rng = Rng(0xC0FFEE);
+        let (rom, _) = test_rom();
+
+        // everything the parser follows pointers from
+        let regions = [
+            (0x00C1CF, 1),
+            (0x00AA66, 6),
+            (0x00D000, 0x300),
+            (0x00E977, 6),
+            (0x02B5F0, 0x800),
+        ];
+
+        // wave count bytes, to also try the largest count of 256 waves
+        let wave_counts: Vec<usize> = (0 .. 3).map(|circuit| {
+            file_offset(rom.arena_offset(circuit, 1, VANILLA_LEVEL_OFFSET).unwrap().0)
+        }).collect();
+
+        for _ in 0 .. 500 {
+            let mut image = rom.rom.clone();
+
+            for _ in 0 .. 1 + rng.below(8) {
+                let (address, len) = regions[rng.below(regions.len())];
+                image[file_offset(address) + rng.below(len)] = rng.next() as u8;
+            }
+
+            if rng.below(4) == 0 {
+                image[wave_counts[rng.below(wave_counts.len())]] = 0xFF;
+            }
+
+            if rng.below(8) == 0 {
+                image.truncate(rng.below(image.len()));
+            }
+
+            exercise(Rom{rom: image}, &mut rng);
+        }
+    }
+
+    #[test]
+    fn truncated_rom_is_an_error() {
+        let (mut rom, _) = test_rom();
+        rom.rom.truncate(0x10000);
+
+        assert_eq!(rom.get_level_data(), Err(RomError::OutOfBounds(file_offset(VANILLA_LEVEL_OFFSET.0))));
+    }
+
+    #[test]
+    fn bad_pointer_is_an_error() {
+        let (mut rom, _) = test_rom();
+        let circuit_pointer = file_offset(VANILLA_LEVEL_OFFSET.0);
+        rom.rom[circuit_pointer .. circuit_pointer + 2].copy_from_slice(&[0x00, 0x10]);
+
+        // the first arena pointer is read from the tv studio entry after it
+        assert_eq!(rom.get_level_data(), Err(RomError::MalformedAddress(SnesAddr(0x021002))));
+    }
+
+    #[test]
+    fn reads_pointers() {
+        let (mut rom, _) = test_rom();
+        rom.write(SnesAddr(0x00D800), &[0xF0, 0xB5, 0x02, 0x00]).unwrap();
+
+        assert_eq!(rom.read_ptr16(SnesAddr(0x00D800), 0x02), Ok(SnesAddr(0x02B5F0)));
+        assert_eq!(rom.read_ptr24(SnesAddr(0x00D800)), Ok(SnesAddr(0x02B5F0)));
+        assert_eq!(rom.read_ptr24(SnesAddr(0x00D801)), Ok(SnesAddr(0x0002B5)));
+        assert_eq!(rom.read_ptr24(SnesAddr(0x0FFFFE)), Err(RomError::OutOfBounds(0x7FFFE)));
+    }
+}
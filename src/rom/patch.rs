@@ -0,0 +1,128 @@
+use super::{address::SnesAddr, Rom, RomError};
+
+pub struct PatchWrite {
+    pub address: SnesAddr,
+    pub original: &'static [u8],
+    pub patched: &'static [u8],
+}
+
+pub struct Patch {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub writes: &'static [PatchWrite],
+}
+
+#[derive(PartialEq, Debug)]
+pub enum PatchState {
+    Original,
+    Applied,
+    Conflict(SnesAddr),
+}
+
+pub const LEVEL_LOADER: Patch = Patch {
+    name: "Expanded level data",
+    description: "Expands the rom to 1MB and loads level data from bank 10 instead of bank 02.",
+    writes: &[
+        // rom size in the header
+        PatchWrite{address: SnesAddr(0x00FFD7), original: &[0x09], patched: &[0x0A]},
+
+        // lda #$02 -> lda #$10
+        PatchWrite{address: SnesAddr(0x00C1CE), original: &[0xA9, 0x02], patched: &[0xA9, 0x10]},
+
+        // lda.w $B5F0 -> lda.w $8000
+        PatchWrite{address: SnesAddr(0x00C1D7), original: &[0xAD, 0xF0, 0xB5], patched: &[0xAD, 0x00, 0x80]},
+
+        // lda.w $B5F1 -> lda.w $8001
+        PatchWrite{address: SnesAddr(0x00C1DC), original: &[0xAD, 0xF1, 0xB5], patched: &[0xAD, 0x01, 0x80]},
+    ],
+};
+
+impl Rom {
+    pub fn patch_state(&self, patch: &Patch) -> Result<PatchState, RomError> {
+        let mut original = true;
+        let mut applied = true;
+
+        for write in patch.writes {
+            let bytes = self.read(write.address, write.patched.len())?;
+
+            original &= bytes == write.original;
+            applied &= bytes == write.patched;
+
+            if !original && !applied {
+                return Ok(PatchState::Conflict(write.address));
+            }
+        }
+
+        Ok(if applied { PatchState::Applied } else { PatchState::Original })
+    }
+
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), RomError> {
+        match self.patch_state(patch)? {
+            PatchState::Applied => Ok(()),
+            PatchState::Conflict(address) => Err(RomError::PatchConflict{patch: patch.name, address}),
+            PatchState::Original => {
+                for write in patch.writes {
+                    self.write(write.address, write.patched)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn revert_patch(&mut self, patch: &Patch) -> Result<(), RomError> {
+        match self.patch_state(patch)? {
+            PatchState::Original => Ok(()),
+            PatchState::Conflict(address) => Err(RomError::PatchConflict{patch: patch.name, address}),
+            PatchState::Applied => {
+                for write in patch.writes {
+                    self.write(write.address, write.original)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_rom, SaveMode};
+
+    #[test]
+    fn detects_patch_state() {
+        let mut rom = test_rom::rom();
+        assert_eq!(rom.patch_state(&LEVEL_LOADER), Ok(PatchState::Original));
+
+        rom.write_level_data(&test_rom::levels(), SaveMode::Expand).unwrap();
+        assert_eq!(rom.patch_state(&LEVEL_LOADER), Ok(PatchState::Applied));
+
+        // applying twice is fine
+        rom.apply_patch(&LEVEL_LOADER).unwrap();
+        assert_eq!(rom.patch_state(&LEVEL_LOADER), Ok(PatchState::Applied));
+
+        rom.revert_patch(&LEVEL_LOADER).unwrap();
+        assert_eq!(rom.patch_state(&LEVEL_LOADER), Ok(PatchState::Original));
+    }
+
+    #[test]
+    fn refuses_conflicting_patch() {
+        let mut rom = test_rom::rom();
+
+        // another hack changed the loader's bank
+        rom.write(SnesAddr(0x00C1CF), &[0x20]).unwrap();
+        let conflict = Err(RomError::PatchConflict{patch: LEVEL_LOADER.name, address: SnesAddr(0x00C1CE)});
+
+        assert_eq!(rom.patch_state(&LEVEL_LOADER), Ok(PatchState::Conflict(SnesAddr(0x00C1CE))));
+        assert_eq!(rom.apply_patch(&LEVEL_LOADER), conflict);
+        assert_eq!(rom.revert_patch(&LEVEL_LOADER), conflict);
+    }
+
+    #[test]
+    fn save_refuses_conflicting_rom() {
+        let mut rom = test_rom::rom();
+        rom.write(SnesAddr(0x00C1D8), &[0x34, 0x12]).unwrap();
+
+        let levels = test_rom::levels();
+        assert!(matches!(rom.with_level_data(&levels, SaveMode::Expand, &[]), Err(RomError::PatchConflict{..})));
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use super::{address::SnesAddr, asm::{arena_label, circuit_label}, charset, Rom, RomError};
+
+pub struct Symbol {
+    pub address: SnesAddr,
+    pub size: usize,
+    pub label: String,
+}
+
+impl Symbol {
+    fn new(address: SnesAddr, size: usize, label: String) -> Self {
+        Self { address, size, label }
+    }
+}
+
+impl Rom {
+    pub fn symbols(&self) -> Result<Vec<Symbol>, RomError> {
+        let level_offset = self.level_offset()?;
+        let levels = self.get_level_data()?;
+
+        let mut symbols = vec![
+            Symbol::new(level_offset, 3 * 2, "level_table".to_string()),
+            Symbol::new(SnesAddr(0x00AA66), 3, "connection_lists_lo".to_string()),
+            Symbol::new(SnesAddr(0x00AA69), 3, "connection_lists_hi".to_string()),
+            Symbol::new(SnesAddr(0x00E977), 3 * 2, "name_lists".to_string()),
+        ];
+
+        for circuit in 0 .. 3 {
+            let arena_count = levels.iter().filter(|level| level.circuit == circuit).count();
+
+            let arena_list = self.read_ptr16(level_offset + circuit as usize * 2, level_offset.bank())?;
+            symbols.push(Symbol::new(arena_list, (1 + arena_count) * 2, circuit_label(circuit)));
+
+            let connection_list = self.connection_list(circuit)?;
+            symbols.push(Symbol::new(connection_list, (1 + arena_count) * 3, format!("{}_connections", circuit_label(circuit))));
+
+            let name_list = self.name_list(circuit)?;
+            symbols.push(Symbol::new(name_list, arena_count * charset::NAME_LENGTH, format!("{}_names", circuit_label(circuit))));
+        }
+
+        for level in &levels {
+            let label = arena_label(level);
+            let base = self.arena_offset(level.circuit, level.arena, level_offset)?;
+
+            symbols.push(Symbol::new(base, 1, label.clone()));
+
+            for wave in 0 .. level.waves.len() {
+                symbols.push(Symbol::new(base + 1 + wave * 10, 10, format!("{}_wave{:02}", label, wave + 1)));
+            }
+
+            symbols.push(Symbol::new(base + 1 + level.waves.len() * 10, 1, format!("{}_waves_remaining", label)));
+
+            let connections = self.connection_list(level.circuit)? + level.arena as usize * 3;
+            symbols.push(Symbol::new(connections, 3, format!("{}_connections", label)));
+
+            let name = self.name_list(level.circuit)? + (level.arena - 1) as usize * charset::NAME_LENGTH;
+            symbols.push(Symbol::new(name, charset::NAME_LENGTH, format!("{}_name", label)));
+        }
+
+        symbols.sort_by_key(|symbol| symbol.address);
+        Ok(symbols)
+    }
+
+    pub fn sym_file(&self) -> Result<String, RomError> {
+        let mut sym = String::from("[labels]\n");
+
+        for symbol in self.symbols()? {
+            let _ = writeln!(sym, "{:02x}:{:04x} {}", symbol.address.bank(), symbol.address.offset(), symbol.label);
+        }
+
+        Ok(sym)
+    }
+
+    // mesen uses file offsets, with a range for multi-byte data
+    pub fn mlb_file(&self) -> Result<String, RomError> {
+        let mut mlb = String::new();
+
+        for symbol in self.symbols()? {
+            let offset = self.file_offset(symbol.address)?;
+
+            let _ = if symbol.size > 1 {
+                writeln!(mlb, "SnesPrgRom:{:X}-{:X}:{}", offset, offset + symbol.size - 1, symbol.label)
+            } else {
+                writeln!(mlb, "SnesPrgRom:{:X}:{}", offset, symbol.label)
+            };
+        }
+
+        Ok(mlb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_rom, SaveMode};
+
+    #[test]
+    fn labels_every_structure() {
+        let levels = test_rom::levels();
+        let mut rom = test_rom::rom();
+
+        let waves: usize = levels.iter().map(|level| level.waves.len()).sum();
+        assert_eq!(rom.symbols().unwrap().len(), 4 + 3 * 3 + 52 * 4 + waves);
+
+        let sym = rom.sym_file().unwrap();
+        assert!(sym.starts_with("[labels]\n"));
+        assert!(sym.contains("\n02:b5f0 level_table\n"));
+
+        rom.write_level_data(&levels, SaveMode::Expand).unwrap();
+        assert!(rom.sym_file().unwrap().contains("\n10:8000 level_table\n"));
+        assert!(rom.mlb_file().unwrap().contains("\nSnesPrgRom:80000-80005:level_table\n"));
+    }
+}
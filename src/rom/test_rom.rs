@@ -0,0 +1,136 @@
+// a synthetic stand-in for the rom, so tests don't need the real thing.
+// only the parts the editor reads are filled in.
+
+use super::{address::{LoRom, Mapper, SnesAddr}, charset, circuit_arena_name, enemy_type::EnemyType, LevelData, Rom, Wave};
+
+const CONNECTION_LISTS: u32 = 0x00D000;
+
+const NAMES: u32 = 0x00E97D;
+
+// stands in for whatever follows the level data in bank 02, saving in place must leave it alone
+pub const FOLLOWING_DATA: [u8; 4] = [0x4C, 0x00, 0x80, 0x60];
+
+pub fn levels() -> Vec<LevelData> {
+    let mut levels = Vec::new();
+
+    let circuit_arena_counts = [11, 18, 23];
+    for (circuit, &arena_max) in circuit_arena_counts.iter().enumerate() {
+        for arena in 1 ..= arena_max {
+            let idx = levels.len();
+
+            let waves = (0 .. 1 + idx % 4).map(|w| Wave {
+                enemy: EnemyType::enemy_list()[(idx + w) % 21].clone(),
+                count: 10 + idx as u16 * 3,
+                spawn_limit: 1 + w as u8,
+                unknown: w as u8 % 2,
+                cooldown_timer: 30 + w as u16 * 15,
+                pre_spawned: w as u8,
+                spawn_timer: 60 * w as u16,
+            }).collect();
+
+            let next = if arena == arena_max { 0xFF } else { arena + 1 };
+
+            levels.push(LevelData {
+                circuit: circuit as u8,
+                arena,
+                name: charset::fit_name(circuit_arena_name()[idx], true),
+                waves,
+                waves_remaining: (idx % 2) as u8,
+                connections: [0, next, arena - 1],
+            });
+        }
+    }
+
+    levels
+}
+
+pub fn rom() -> Rom {
+    Rom{rom: build(&levels())}
+}
+
+pub fn build(levels: &[LevelData]) -> Vec<u8> {
+    let mut rom = vec![0; 0x80000];
+
+    // reset routine start, checked by Rom::is_smash_tv
+    rom[0 .. 3].copy_from_slice(&[0x78, 0x9C, 0x00]);
+
+    // header rom size and the original level data loader
+    write(&mut rom, 0x00FFD7, &[0x09]);
+    write(&mut rom, 0x00C1CE, &[0xA9, 0x02]);
+    write(&mut rom, 0x00C1D7, &[0xAD, 0xF0, 0xB5]);
+    write(&mut rom, 0x00C1DC, &[0xAD, 0xF1, 0xB5]);
+
+    let table = level_table(levels, 0xB5F0);
+    write(&mut rom, 0x02B5F0, &table);
+    write(&mut rom, 0x02B5F0 + table.len() as u32, &FOLLOWING_DATA);
+
+    for circuit in 0 .. 3u8 {
+        let arenas: Vec<&LevelData> = levels.iter().filter(|level| level.circuit == circuit).collect();
+
+        // connection lists, pointed to by separate low and high byte tables.
+        // the first entry of each list belongs to the tv studio.
+        let list = CONNECTION_LISTS + circuit as u32 * 0x100;
+        write(&mut rom, 0x00AA66 + circuit as u32, &[list as u8]);
+        write(&mut rom, 0x00AA69 + circuit as u32, &[(list >> 8) as u8]);
+
+        for level in &arenas {
+            write(&mut rom, list + level.arena as u32 * 3, &level.connections);
+        }
+
+        // name pointers
+        let arenas_before = levels.iter().filter(|level| level.circuit < circuit).count();
+        let names = NAMES + (arenas_before * charset::NAME_LENGTH) as u32;
+        write(&mut rom, 0x00E977 + circuit as u32 * 2, &(names as u16).to_le_bytes());
+    }
+
+    for (idx, level) in levels.iter().enumerate() {
+        write(&mut rom, NAMES + (idx * charset::NAME_LENGTH) as u32, &charset::encode(&level.name));
+    }
+
+    rom
+}
+
+// arenas are stored last to first, so nothing can rely on them being in order
+fn level_table(levels: &[LevelData], base: u16) -> Vec<u8> {
+    let pointers_size = (3 + 3 + levels.len()) * 2;
+
+    let mut offsets = vec![0; levels.len()];
+    let mut arenas = Vec::new();
+
+    for (idx, level) in levels.iter().enumerate().rev() {
+        offsets[idx] = pointers_size + arenas.len();
+
+        arenas.push((level.waves.len() - 1) as u8);
+        for wave in &level.waves {
+            arenas.push(wave.enemy.to_u8());
+            arenas.extend_from_slice(&wave.count.to_le_bytes());
+            arenas.push(wave.spawn_limit);
+            arenas.push(wave.unknown);
+            arenas.extend_from_slice(&wave.cooldown_timer.to_le_bytes());
+            arenas.push(wave.pre_spawned);
+            arenas.extend_from_slice(&wave.spawn_timer.to_le_bytes());
+        }
+        arenas.push(level.waves_remaining);
+    }
+
+    let mut circuit_pointers = Vec::new();
+    let mut arena_pointers = Vec::new();
+
+    for circuit in 0 .. 3u8 {
+        circuit_pointers.extend_from_slice(&(base + 6 + arena_pointers.len() as u16).to_le_bytes());
+        arena_pointers.extend_from_slice(&[0, 0]);
+
+        for (idx, _) in levels.iter().enumerate().filter(|(_, level)| level.circuit == circuit) {
+            arena_pointers.extend_from_slice(&(base + offsets[idx] as u16).to_le_bytes());
+        }
+    }
+
+    circuit_pointers.append(&mut arena_pointers);
+    circuit_pointers.append(&mut arenas);
+    circuit_pointers
+}
+
+fn write(rom: &mut [u8], address: u32, bytes: &[u8]) {
+    let offset = LoRom.to_file_offset(SnesAddr(address)).unwrap();
+    rom[offset .. offset + bytes.len()].copy_from_slice(bytes);
+}
@@ -0,0 +1,89 @@
+use std::fmt::Write;
+
+use super::{circuit_arena_name, enemy_type::EnemyType, LevelData, Wave};
+
+pub struct Usage<'a> {
+    pub level: usize,
+    pub wave: usize,
+    pub data: &'a Wave,
+}
+
+// known enemies are listed even when unused, unknown ids follow in the order found
+pub fn enemy_usage(level_data: &[LevelData]) -> Vec<(EnemyType, Vec<Usage<'_>>)> {
+    let mut usage: Vec<(EnemyType, Vec<Usage<'_>>)> = EnemyType::enemy_list().into_iter().map(|enemy| (enemy, Vec::new())).collect();
+
+    for (level_idx, level) in level_data.iter().enumerate() {
+        for (wave_idx, wave) in level.waves.iter().enumerate() {
+            let entry = Usage{level: level_idx, wave: wave_idx, data: wave};
+
+            match usage.iter_mut().find(|(enemy, _)| enemy == &wave.enemy) {
+                Some((_, entries)) => entries.push(entry),
+                None => usage.push((wave.enemy.clone(), vec![entry])),
+            }
+        }
+    }
+
+    usage
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn usage_csv(level_data: &[LevelData]) -> String {
+    let mut csv = String::from("enemy,enemy_id,arena,circuit,arena_number,wave,count,spawn_limit,unknown,cooldown_timer,pre_spawned,spawn_timer\n");
+
+    for (enemy, entries) in enemy_usage(level_data) {
+        for usage in entries {
+            let level = &level_data[usage.level];
+            let wave = usage.data;
+
+            let _ = writeln!(
+                csv, "{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&enemy.name()), enemy.to_u8(), csv_field(circuit_arena_name()[usage.level]),
+                level.circuit + 1, level.arena, usage.wave + 1, wave.count, wave.spawn_limit,
+                wave.unknown, wave.cooldown_timer, wave.pre_spawned, wave.spawn_timer,
+            );
+        }
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_rom;
+
+    #[test]
+    fn lists_every_wave_once() {
+        let mut levels = test_rom::levels();
+        levels[7].waves[0].enemy = EnemyType::Unknown(19);
+
+        let usage = enemy_usage(&levels);
+        let waves: usize = levels.iter().map(|level| level.waves.len()).sum();
+
+        assert_eq!(usage.iter().map(|(_, entries)| entries.len()).sum::<usize>(), waves);
+        assert_eq!(usage.len(), 22);
+        assert_eq!(usage[21].0, EnemyType::Unknown(19));
+        assert_eq!((usage[21].1[0].level, usage[21].1[0].wave), (7, 0));
+
+        for (enemy, entries) in &usage {
+            assert!(entries.iter().all(|entry| &levels[entry.level].waves[entry.wave].enemy == enemy));
+        }
+    }
+
+    #[test]
+    fn exports_csv() {
+        let levels = test_rom::levels();
+        let csv = usage_csv(&levels);
+        let waves: usize = levels.iter().map(|level| level.waves.len()).sum();
+
+        assert_eq!(csv.lines().count(), 1 + waves);
+        assert_eq!(csv_field("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+}
@@ -1,8 +1,7 @@
 lorom
-
-org $007FD7 : db $0A ; db $09 | expand rom size to 1MB
-
-; load level data changes
-org $00C1CE : lda #$10 ; lda #$02 | load wave data from bank 10 instead of 02
-org $00C1D7 : lda.w $8000 ; lda.w $B5F0 | change level data offset
-org $00C1DC : lda.w $8001 ; lda.w $B5F1 | ^
+; Expands the rom to 1MB and loads level data from bank 10 instead of bank 02.
+org $00FFD7 : db $0A ; was $09
+org $00C1CE : db $A9, $10 ; was $A9, $02
+org $00C1D7 : db $AD, $00, $80 ; was $AD, $F0, $B5
+org $00C1DC : db $AD, $01, $80 ; was $AD, $F1, $B5
+org $1FFFFF : db $00